@@ -1,4 +1,4 @@
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LspClient {
@@ -13,6 +13,20 @@ pub struct Cli {
     #[clap(short, long)]
     pub client: Option<LspClient>,
 
-    #[clap(long, value_parser, num_args = 1.., value_delimiter = ',')]
+    #[clap(long, value_parser, num_args = 1.., value_delimiter = ',', global = true)]
     pub search_dirs: Vec<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Commands that run against the search dirs and exit instead of starting the language server
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Report how many types, fields and variants are documented in each module and package
+    DocCoverage {
+        /// Exit with an error when less than this percentage of the workspace is documented
+        #[clap(long)]
+        fail_under: Option<f64>,
+    },
 }
@@ -1,5 +1,6 @@
 use std::time::Duration;
 
+use async_lsp::ClientSocket;
 use async_lsp::client_monitor::ClientProcessMonitorLayer;
 use async_lsp::concurrency::ConcurrencyLayer;
 use async_lsp::panic::CatchUnwindLayer;
@@ -10,7 +11,7 @@ use clap::Parser;
 use tower::ServiceBuilder;
 use tracing::{Level, debug};
 
-use crate::cli::Cli;
+use crate::cli::{Cli, Command};
 use crate::server::{Server, TickEvent};
 
 mod cli;
@@ -22,6 +23,22 @@ mod server;
 async fn main() {
     let cli = Cli::parse();
 
+    if let Some(Command::DocCoverage { fail_under }) = cli.command {
+        let mut server = Server::new(&ClientSocket::new_closed(), (&cli).into());
+        server.initialize_workspace();
+        let coverage = server.documentation_coverage();
+        print!("{}", coverage);
+        if let Some(minimum) = fail_under.filter(|minimum| coverage.percentage() < *minimum) {
+            eprintln!(
+                "documentation coverage of {:.1}% is below the minimum of {:.1}%",
+                coverage.percentage(),
+                minimum
+            );
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let (server, _) = async_lsp::MainLoop::new_server(|client| {
         tokio::spawn({
             let client = client.clone();
@@ -38,6 +38,7 @@ pub enum NodeKind {
     // Annotations
     Annotation,
     Annotations,
+    AnnotationDecorator,
     AnnotationDeclaration,
     FieldReference,
 
@@ -117,6 +118,7 @@ impl NodeKind {
             // Annotations
             "annotation" => Self::Annotation,
             "annotations" => Self::Annotations,
+            "annotation_decorator" => Self::AnnotationDecorator,
             "annotation_declaration" => Self::AnnotationDeclaration,
             "field_reference" => Self::FieldReference,
 
@@ -168,6 +170,7 @@ impl NodeKind {
             // Annotations
             Self::Annotation => "annotation",
             Self::Annotations => "annotations",
+            Self::AnnotationDecorator => "annotation_decorator",
             Self::AnnotationDeclaration => "annotation_declaration",
             Self::FieldReference => "field_reference",
 
@@ -278,6 +281,10 @@ impl NodeKind {
         n.kind() == Self::Annotations.as_str()
     }
 
+    pub fn is_annotation_decorator(n: &Node) -> bool {
+        n.kind() == Self::AnnotationDecorator.as_str()
+    }
+
     pub fn is_annotation_declaration(n: &Node) -> bool {
         n.kind() == Self::AnnotationDeclaration.as_str()
     }
@@ -1,6 +1,8 @@
 mod kind;
+mod primitive;
 
 pub use kind::NodeKind;
+pub use primitive::AdlPrimitive;
 use tracing::warn;
 use tree_sitter::{Node, TreeCursor};
 
@@ -140,12 +142,16 @@ impl<'a> AdlImportDeclaration<'a> {
         }
     }
 
-    pub fn is_missing_semicolon(&self) -> bool {
-        let node = match self {
+    /// The underlying `import_declaration` node
+    pub fn inner(&self) -> &Node<'a> {
+        match self {
             AdlImportDeclaration::FullyQualified(scoped_name) => &scoped_name.node,
             AdlImportDeclaration::StarImport(scoped_name) => &scoped_name.node,
-        };
-        let mut cursor = node.walk();
+        }
+    }
+
+    pub fn is_missing_semicolon(&self) -> bool {
+        let mut cursor = self.inner().walk();
         let last_child = cursor.goto_last_child();
         last_child && cursor.node().kind() != ";"
     }
@@ -0,0 +1,100 @@
+//! The built-in primitive types of ADL, as defined by `primitive_type` in tree-sitter-adl
+
+/// Typed parsing of the keywords that make up a `primitive_type` node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdlPrimitive {
+    // Integers
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Word8,
+    Word16,
+    Word32,
+    Word64,
+
+    // Scalars
+    Bool,
+    Void,
+    Float,
+    Double,
+    String,
+    Bytes,
+    Json,
+
+    // Generics
+    Vector,
+    StringMap,
+    Nullable,
+    TypeToken,
+}
+
+impl AdlPrimitive {
+    pub const ALL: [AdlPrimitive; 19] = [
+        Self::Int8,
+        Self::Int16,
+        Self::Int32,
+        Self::Int64,
+        Self::Word8,
+        Self::Word16,
+        Self::Word32,
+        Self::Word64,
+        Self::Bool,
+        Self::Void,
+        Self::Float,
+        Self::Double,
+        Self::String,
+        Self::Bytes,
+        Self::Json,
+        Self::Vector,
+        Self::StringMap,
+        Self::Nullable,
+        Self::TypeToken,
+    ];
+
+    pub fn from_name(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|p| p.as_str() == s)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            // Integers
+            Self::Int8 => "Int8",
+            Self::Int16 => "Int16",
+            Self::Int32 => "Int32",
+            Self::Int64 => "Int64",
+            Self::Word8 => "Word8",
+            Self::Word16 => "Word16",
+            Self::Word32 => "Word32",
+            Self::Word64 => "Word64",
+
+            // Scalars
+            Self::Bool => "Bool",
+            Self::Void => "Void",
+            Self::Float => "Float",
+            Self::Double => "Double",
+            Self::String => "String",
+            Self::Bytes => "Bytes",
+            Self::Json => "Json",
+
+            // Generics
+            Self::Vector => "Vector",
+            Self::StringMap => "StringMap",
+            Self::Nullable => "Nullable",
+            Self::TypeToken => "TypeToken",
+        }
+    }
+
+    /// Whether the primitive must be instantiated with a type argument e.g. `Vector<T>`
+    pub fn is_generic(&self) -> bool {
+        matches!(
+            self,
+            Self::Vector | Self::StringMap | Self::Nullable | Self::TypeToken
+        )
+    }
+
+    /// The number of type arguments the primitive must be instantiated with
+    pub fn type_parameter_count(&self) -> usize {
+        if self.is_generic() { 1 } else { 0 }
+    }
+}
@@ -0,0 +1,234 @@
+use async_lsp::lsp_types::{CompletionItem, CompletionItemKind, Position};
+use tree_sitter::Node;
+
+use crate::node::{AdlImportDeclaration, AdlPrimitive, NodeKind};
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+
+pub trait Completion {
+    /// Check whether a type expression can be written at the given position
+    fn is_type_position(&self, pos: &Position) -> bool;
+
+    /// Collect the type names that are visible from the given position without consulting other documents
+    fn type_completions(&self, pos: &Position, content: &[u8]) -> Vec<CompletionItem>;
+}
+
+impl Completion for ParsedTree {
+    fn is_type_position(&self, pos: &Position) -> bool {
+        self.get_completion_node_at(pos)
+            .is_some_and(|n| Self::is_type_position_impl(&n))
+    }
+
+    fn type_completions(&self, pos: &Position, content: &[u8]) -> Vec<CompletionItem> {
+        let module_name = self.find_module_name(content).unwrap_or_default();
+        let mut items = vec![];
+
+        // types defined in this module
+        items.extend(
+            self.find_all_nodes(NodeKind::is_local_definition)
+                .into_iter()
+                .filter_map(|n| {
+                    Self::type_completion_item_from_definition(&n, module_name, content)
+                }),
+        );
+
+        // type parameters of the enclosing definition
+        if let Some(definition) = self
+            .get_completion_node_at(pos)
+            .and_then(|n| Self::find_enclosing_definition(&n))
+        {
+            items.extend(
+                Self::type_parameter_names(&definition, content)
+                    .into_iter()
+                    .map(|name| CompletionItem {
+                        label: name.to_string(),
+                        kind: Some(CompletionItemKind::TYPE_PARAMETER),
+                        detail: Some("type parameter".to_string()),
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        // explicitly imported types, even if the import could not be resolved
+        items.extend(
+            self.find_all_nodes(NodeKind::is_import_declaration)
+                .into_iter()
+                .filter_map(AdlImportDeclaration::try_new)
+                .filter_map(|import| {
+                    import
+                        .imported_type_name(content)
+                        .map(|type_name| CompletionItem {
+                            label: type_name.to_string(),
+                            kind: Some(CompletionItemKind::CLASS),
+                            detail: Some(import.module_name(content).to_string()),
+                            ..Default::default()
+                        })
+                }),
+        );
+
+        items.extend(
+            AdlPrimitive::ALL
+                .into_iter()
+                .map(|primitive| CompletionItem {
+                    label: primitive.as_str().to_string(),
+                    kind: Some(CompletionItemKind::KEYWORD),
+                    detail: Some("primitive".to_string()),
+                    ..Default::default()
+                }),
+        );
+
+        items
+    }
+}
+
+impl ParsedTree {
+    /// Build a completion item for a type defined in this document
+    pub fn type_completion_item(&self, type_name: &str, content: &[u8]) -> Option<CompletionItem> {
+        let module_name = self.find_module_name(content).unwrap_or_default();
+        self.find_all_nodes(NodeKind::is_local_definition)
+            .into_iter()
+            .find(|n| Self::definition_name(n, content) == Some(type_name))
+            .and_then(|n| Self::type_completion_item_from_definition(&n, module_name, content))
+    }
+
+    fn type_completion_item_from_definition(
+        definition: &Node<'_>,
+        module_name: &str,
+        content: &[u8],
+    ) -> Option<CompletionItem> {
+        // NOTE: mirrors the symbol kinds used for document symbols
+        let kind = match NodeKind::from_kind(definition.kind()) {
+            NodeKind::StructDefinition => CompletionItemKind::STRUCT,
+            NodeKind::UnionDefinition => CompletionItemKind::ENUM,
+            _ => CompletionItemKind::CLASS,
+        };
+
+        Self::definition_name(definition, content).map(|name| CompletionItem {
+            label: name.to_string(),
+            kind: Some(kind),
+            detail: Some(module_name.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Find the node that the cursor is in, preferring the node just before the cursor
+    /// so that a partially typed identifier is found
+    fn get_completion_node_at(&self, pos: &Position) -> Option<Node<'_>> {
+        if pos.character > 0 {
+            let before = Position {
+                line: pos.line,
+                character: pos.character - 1,
+            };
+            if let Some(node) = self
+                .get_node_at_position(&before)
+                .filter(NodeKind::is_identifier)
+            {
+                return Some(node);
+            }
+        }
+        self.get_node_at_position(pos)
+    }
+
+    fn is_type_position_impl(n: &Node<'_>) -> bool {
+        // the name of a field is not a type
+        if NodeKind::is_identifier(n) && n.parent().is_some_and(|p| NodeKind::is_field(&p)) {
+            return false;
+        }
+
+        let mut node = Some(*n);
+        while let Some(current) = node {
+            match NodeKind::from_kind(current.kind()) {
+                NodeKind::TypeExpression | NodeKind::TypeArguments => return true,
+                NodeKind::JsonValue
+                | NodeKind::Comment
+                | NodeKind::Docstring
+                | NodeKind::DefinitionPreamble
+                | NodeKind::TypeName
+                | NodeKind::TypeParameters
+                | NodeKind::ImportDeclaration
+                | NodeKind::AnnotationDeclaration
+                | NodeKind::ModuleBody => return false,
+                // the start of a new field is always a type
+                NodeKind::FieldBlock => return true,
+                _ => {}
+            }
+            node = current.parent();
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::{CompletionItemKind, Position, Url};
+
+    use crate::parser::{AdlParser, completion::Completion};
+
+    const SAMPLE_ADL: &str = r#"module sample.completion {
+    import common.db.User;
+    import common.strings.*;
+
+    struct Pair<A, B> {
+        A first;
+        Vector<B> second = [];
+    };
+
+    union Shape {
+        Void circle;
+    };
+
+    type Name = String;
+};
+"#;
+
+    #[test]
+    fn test_is_type_position() {
+        let uri: Url = "file://input/completion.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, SAMPLE_ADL).unwrap();
+
+        // field type
+        assert!(tree.is_type_position(&Position::new(5, 9)));
+        // type argument
+        assert!(tree.is_type_position(&Position::new(6, 16)));
+        // empty line in a field block
+        assert!(tree.is_type_position(&Position::new(7, 0)));
+        // aliased type
+        assert!(tree.is_type_position(&Position::new(13, 18)));
+
+        // field name
+        assert!(!tree.is_type_position(&Position::new(5, 12)));
+        // default value
+        assert!(!tree.is_type_position(&Position::new(6, 28)));
+        // import declaration
+        assert!(!tree.is_type_position(&Position::new(1, 14)));
+        // definition name
+        assert!(!tree.is_type_position(&Position::new(4, 12)));
+    }
+
+    #[test]
+    fn test_type_completions() {
+        let uri: Url = "file://input/completion.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, SAMPLE_ADL).unwrap();
+
+        let items = tree.type_completions(&Position::new(5, 9), SAMPLE_ADL.as_bytes());
+        let item = |label: &str| items.iter().find(|i| i.label == label);
+
+        assert_eq!(item("Pair").unwrap().kind, Some(CompletionItemKind::STRUCT));
+        assert_eq!(item("Shape").unwrap().kind, Some(CompletionItemKind::ENUM));
+        assert_eq!(item("Name").unwrap().kind, Some(CompletionItemKind::CLASS));
+        assert_eq!(
+            item("A").unwrap().kind,
+            Some(CompletionItemKind::TYPE_PARAMETER)
+        );
+        assert_eq!(item("User").unwrap().detail, Some("common.db".to_string()));
+        assert_eq!(
+            item("StringMap").unwrap().kind,
+            Some(CompletionItemKind::KEYWORD)
+        );
+
+        // type parameters are only in scope within their definition
+        let items = tree.type_completions(&Position::new(11, 9), SAMPLE_ADL.as_bytes());
+        assert!(!items.iter().any(|i| i.label == "A"));
+    }
+}
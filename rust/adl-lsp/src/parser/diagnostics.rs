@@ -1,12 +1,19 @@
-use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Range};
+use std::collections::{HashMap, HashSet};
+
+use async_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location,
+    NumberOrString, Range,
+};
 use tracing::debug;
+use tree_sitter::Node;
 
 use crate::node::{
     AdlAnnotationDeclaration, AdlField, AdlImportDeclaration, AdlModuleBody, AdlModuleDefinition,
-    AdlNewtypeDefinition, AdlStructDefinition, AdlTypeDefinition, AdlUnionDefinition, NodeKind,
+    AdlNewtypeDefinition, AdlPrimitive, AdlStructDefinition, AdlTypeDefinition, AdlUnionDefinition,
+    NodeKind,
 };
 use crate::parser::tree::Tree;
-use crate::parser::ts_lsp_interop::ts_to_lsp_position;
+use crate::parser::ts_lsp_interop::{ts_to_lsp_position, ts_to_lsp_range};
 
 use super::ParsedTree;
 
@@ -26,12 +33,15 @@ impl ParsedTree {
         self.collect_parse_diagnostics_missing(&mut diagnostics);
 
         // then collect custom semantic errors
-        if let Some(import_diagnostics) = self.collect_import_diagnostics() {
+        if let Some(import_diagnostics) = self.collect_import_diagnostics(content.as_bytes()) {
             diagnostics.extend(import_diagnostics);
         }
         if let Some(missing_semicolon_diagnostics) = self.collect_missing_semicolon_diagnostics() {
             diagnostics.extend(missing_semicolon_diagnostics);
         }
+        diagnostics.extend(self.collect_duplicate_definition_diagnostics(content.as_bytes()));
+        diagnostics.extend(self.collect_field_diagnostics(content.as_bytes()));
+        diagnostics.extend(self.collect_primitive_arity_diagnostics());
 
         debug!("collected diagnostics: {:?}", diagnostics);
         diagnostics
@@ -190,7 +200,7 @@ impl ParsedTree {
         Some(diagnostics)
     }
 
-    pub fn collect_import_diagnostics(&self) -> Option<Vec<Diagnostic>> {
+    pub fn collect_import_diagnostics(&self, content: &[u8]) -> Option<Vec<Diagnostic>> {
         let imports = self.find_all_nodes(NodeKind::is_import_declaration);
 
         let module_body = AdlModuleBody::try_new(self.find_first_node(NodeKind::is_module_body)?)?;
@@ -209,13 +219,13 @@ impl ParsedTree {
             }
         }
 
-        let first_non_import = first_non_import?;
-
-        let out_of_order_imports = imports
+        let mut diagnostics: Vec<Diagnostic> = imports
             .iter()
             .filter_map(|node| {
                 // imports should only be at the top of a module
-                if node.start_position() > first_non_import.start_position() {
+                if first_non_import
+                    .is_some_and(|first| node.start_position() > first.start_position())
+                {
                     Some(Diagnostic {
                         range: Range {
                             start: ts_to_lsp_position(&node.start_position()),
@@ -232,10 +242,351 @@ impl ParsedTree {
             })
             .collect();
 
-        // TODO(med): attempt to resolve imports and report errors for invalid imports
-        // TODO(med): check for unused or duplicate imports
+        // NOTE: unresolved imports are reported by the ImportsCache which can see the rest of the workspace
+        // and so are unused star imports, since the types they bring into scope are defined elsewhere
+        let references = self.find_unqualified_references(content);
+        let mut seen_imports = HashSet::new();
+        for node in &imports {
+            let Some(import) = AdlImportDeclaration::try_new(*node) else {
+                continue;
+            };
+            let module_name = import.module_name(content);
+            let imported_type_name = import.imported_type_name(content);
+
+            if !seen_imports.insert((module_name, imported_type_name)) {
+                let message = match imported_type_name {
+                    Some(type_name) => {
+                        format!("`{}.{}` is imported more than once", module_name, type_name)
+                    }
+                    None => format!("`{}.*` is imported more than once", module_name),
+                };
+                diagnostics.push(Self::unnecessary_import_diagnostic(
+                    node,
+                    "duplicate-import",
+                    message,
+                ));
+            } else if let Some(type_name) =
+                imported_type_name.filter(|type_name| !references.contains(type_name))
+            {
+                diagnostics.push(Self::unnecessary_import_diagnostic(
+                    node,
+                    "unused-import",
+                    format!("`{}.{}` is imported but never used", module_name, type_name),
+                ));
+            }
+        }
+
+        Some(diagnostics)
+    }
+
+    /// Report every definition whose name has already been defined earlier in the module
+    pub fn collect_duplicate_definition_diagnostics(&self, content: &[u8]) -> Vec<Diagnostic> {
+        let mut first_definitions = HashMap::<&str, Node>::new();
+        let mut diagnostics = vec![];
+
+        for type_name in self
+            .find_all_nodes(NodeKind::is_type_name)
+            .into_iter()
+            .filter(|n| {
+                n.parent()
+                    .is_some_and(|p| NodeKind::is_local_definition(&p))
+            })
+        {
+            let Ok(name) = type_name.utf8_text(content) else {
+                continue;
+            };
+            let Some(first_definition) = first_definitions.get(name) else {
+                first_definitions.insert(name, type_name);
+                continue;
+            };
+
+            diagnostics.push(self.conflict_diagnostic(
+                &type_name,
+                "duplicate-definition",
+                format!("`{}` is defined more than once in this module", name),
+                first_definition,
+                format!("`{}` is first defined here", name),
+            ));
+        }
+
+        diagnostics
+    }
+
+    /// Report fields that share a name within a struct or union, and fields whose JSON keys collide
+    /// once `SerializedName` annotations (inline or declared in this module) are applied
+    pub fn collect_field_diagnostics(&self, content: &[u8]) -> Vec<Diagnostic> {
+        let declared_serialized_names = self.find_declared_serialized_names(content);
+        let mut diagnostics = vec![];
+
+        for definition in self.find_all_nodes(|n| {
+            NodeKind::is_struct_definition(n) || NodeKind::is_union_definition(n)
+        }) {
+            let Some(type_name) = Self::definition_name(&definition, content) else {
+                continue;
+            };
+            let fields: Vec<Node> = definition
+                .children(&mut definition.walk())
+                .find(NodeKind::is_field_block)
+                .map(|field_block| {
+                    field_block
+                        .named_children(&mut field_block.walk())
+                        .filter(NodeKind::is_field)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let mut first_fields = HashMap::<&str, Node>::new();
+            // JSON key -> the field using it and the node that sets the key
+            let mut json_keys = HashMap::<String, (&str, Node)>::new();
+
+            for field in fields {
+                let Some(name_node) = Self::field_name_node(&field) else {
+                    continue;
+                };
+                let Ok(name) = name_node.utf8_text(content) else {
+                    continue;
+                };
+
+                if let Some(first_field) = first_fields.get(name) {
+                    diagnostics.push(self.conflict_diagnostic(
+                        &name_node,
+                        "duplicate-field",
+                        format!("`{}` has more than one field named `{}`", type_name, name),
+                        first_field,
+                        format!("`{}` is first defined here", name),
+                    ));
+                    continue;
+                }
+                first_fields.insert(name, name_node);
+
+                let (json_key, key_node) = Self::serialized_name(&field, content)
+                    .or_else(|| declared_serialized_names.get(&(type_name, name)).copied())
+                    .and_then(|json_value| {
+                        Some((Self::json_string_value(&json_value, content)?, json_value))
+                    })
+                    .unwrap_or_else(|| (name.to_string(), name_node));
+
+                match json_keys.get(&json_key) {
+                    Some((other_name, other_key_node)) => {
+                        diagnostics.push(self.conflict_diagnostic(
+                            &key_node,
+                            "serialized-name-collision",
+                            format!(
+                                "field `{}` is serialized as \"{}\", which is already the JSON key of field `{}`",
+                                name, json_key, other_name
+                            ),
+                            other_key_node,
+                            format!("`{}` is serialized as \"{}\" here", other_name, json_key),
+                        ));
+                    }
+                    None => {
+                        json_keys.insert(json_key, (name, key_node));
+                    }
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Report primitives instantiated with the wrong number of type arguments, including scalars given
+    /// type arguments and generic primitives used bare, which the grammar only reports as syntax errors
+    pub fn collect_primitive_arity_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        for primitive_type in self.find_all_nodes(NodeKind::is_primitive_type) {
+            let Some(primitive) = primitive_type
+                .child(0)
+                .and_then(|keyword| AdlPrimitive::from_name(keyword.kind()))
+            else {
+                continue;
+            };
+            let given = primitive_type
+                .children(&mut primitive_type.walk())
+                .find(NodeKind::is_type_arguments)
+                .map_or(0, |type_arguments| type_arguments.named_child_count());
+
+            // e.g. `String<Int32>` leaves the type arguments in an error following the type expression
+            let misplaced_type_arguments = primitive_type
+                .parent()
+                .and_then(|type_expression| type_expression.next_sibling())
+                .filter(|n| NodeKind::is_error(n) && n.child(0).is_some_and(|c| c.kind() == "<"));
+
+            if let Some(type_arguments) = misplaced_type_arguments {
+                diagnostics.push(Self::type_arity_diagnostic(
+                    Range::new(
+                        ts_to_lsp_position(&primitive_type.start_position()),
+                        ts_to_lsp_position(&type_arguments.end_position()),
+                    ),
+                    primitive.as_str(),
+                    primitive.type_parameter_count(),
+                    type_arguments.named_child_count(),
+                ));
+            } else if given != primitive.type_parameter_count() {
+                diagnostics.push(Self::type_arity_diagnostic(
+                    ts_to_lsp_range(&primitive_type.range()),
+                    primitive.as_str(),
+                    primitive.type_parameter_count(),
+                    given,
+                ));
+            }
+        }
+
+        // a generic primitive without type arguments is not a `primitive_type` at all
+        for error in self.find_all_nodes(NodeKind::is_error) {
+            let mut cursor = error.walk();
+            let children: Vec<Node> = error.children(&mut cursor).collect();
+            for (keyword, next) in children
+                .iter()
+                .zip(children.iter().skip(1).map(Some).chain([None]))
+            {
+                let Some(primitive) = AdlPrimitive::from_name(keyword.kind()) else {
+                    continue;
+                };
+                if primitive.is_generic() && next.is_none_or(|n| n.kind() != "<") {
+                    diagnostics.push(Self::type_arity_diagnostic(
+                        ts_to_lsp_range(&keyword.range()),
+                        primitive.as_str(),
+                        primitive.type_parameter_count(),
+                        0,
+                    ));
+                }
+            }
+        }
+
+        diagnostics
+    }
+
+    /// An error for a type instantiated with the wrong number of type arguments
+    pub fn type_arity_diagnostic(
+        range: Range,
+        type_name: &str,
+        expected: usize,
+        given: usize,
+    ) -> Diagnostic {
+        let plural = |count: usize| if count == 1 { "" } else { "s" };
+        let message = match (expected, given) {
+            (0, _) => format!("`{}` does not take type arguments", type_name),
+            (_, 0) => format!(
+                "`{}` expects {} type argument{}, but none were given",
+                type_name,
+                expected,
+                plural(expected)
+            ),
+            _ => format!(
+                "`{}` expects {} type argument{}, but {} {} given",
+                type_name,
+                expected,
+                plural(expected),
+                given,
+                if given == 1 { "was" } else { "were" }
+            ),
+        };
+
+        Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(String::from("type-arity"))),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// The values of `annotation Type::field SerializedName "..."` declarations in this module,
+    /// keyed by the type and field names
+    pub fn find_declared_serialized_names<'a>(
+        &'a self,
+        content: &'a [u8],
+    ) -> HashMap<(&'a str, &'a str), Node<'a>> {
+        self.find_all_nodes(NodeKind::is_annotation_declaration)
+            .into_iter()
+            .filter_map(|declaration| {
+                let (annotation_type, json_value) = Self::annotation_type_and_value(&declaration)?;
+                if !Self::is_serialized_name(&annotation_type, content) {
+                    return None;
+                }
+
+                let (type_name, field_name) =
+                    self.annotation_declaration_target(&declaration, content)?;
+                Some(((type_name, field_name?), json_value))
+            })
+            .collect()
+    }
+
+    /// The value of a `@SerializedName "..."` decorator on a field
+    pub fn serialized_name<'a>(field: &Node<'a>, content: &[u8]) -> Option<Node<'a>> {
+        let preamble = field
+            .children(&mut field.walk())
+            .find(NodeKind::is_definition_preamble)?;
+        preamble
+            .named_children(&mut preamble.walk())
+            .filter(NodeKind::is_annotation_decorator)
+            .filter_map(|decorator| Self::annotation_type_and_value(&decorator))
+            .find(|(annotation_type, _)| Self::is_serialized_name(annotation_type, content))
+            .map(|(_, json_value)| json_value)
+    }
+
+    /// Check whether an annotation is `SerializedName`, whether or not it is qualified by `sys.annotations`
+    fn is_serialized_name(annotation_type: &Node, content: &[u8]) -> bool {
+        annotation_type
+            .utf8_text(content)
+            .is_ok_and(|name| name == "SerializedName" || name.ends_with(".SerializedName"))
+    }
 
-        Some(out_of_order_imports)
+    /// The unescaped text of a JSON value that is a string
+    pub fn json_string_value(json_value: &Node, content: &[u8]) -> Option<String> {
+        let json_string = json_value.named_child(0).filter(NodeKind::is_json_string)?;
+        serde_json::from_str(json_string.utf8_text(content).ok()?).ok()
+    }
+
+    /// An error at one node that conflicts with another node in the same document
+    fn conflict_diagnostic(
+        &self,
+        node: &Node,
+        code: &str,
+        message: String,
+        conflicting_node: &Node,
+        conflicting_message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            range: Range {
+                start: ts_to_lsp_position(&node.start_position()),
+                end: ts_to_lsp_position(&node.end_position()),
+            },
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(code.to_string())),
+            message,
+            related_information: Some(vec![DiagnosticRelatedInformation {
+                location: Location {
+                    uri: self.uri.clone(),
+                    range: Range {
+                        start: ts_to_lsp_position(&conflicting_node.start_position()),
+                        end: ts_to_lsp_position(&conflicting_node.end_position()),
+                    },
+                },
+                message: conflicting_message,
+            }]),
+            ..Default::default()
+        }
+    }
+
+    /// A warning for an import that can be removed without changing the meaning of the module
+    pub fn unnecessary_import_diagnostic(
+        import_declaration: &Node,
+        code: &str,
+        message: String,
+    ) -> Diagnostic {
+        Diagnostic {
+            range: Range {
+                start: ts_to_lsp_position(&import_declaration.start_position()),
+                end: ts_to_lsp_position(&import_declaration.end_position()),
+            },
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(code.to_string())),
+            tags: Some(vec![DiagnosticTag::UNNECESSARY]),
+            message,
+            ..Default::default()
+        }
     }
 }
 
@@ -275,4 +626,44 @@ mod test {
         assert!(parsed.is_some());
         assert_yaml_snapshot!(parsed.unwrap().collect_diagnostics(contents));
     }
+
+    #[test]
+    fn test_collect_duplicate_definition_errors() {
+        let url: Url = "file://foo/duplicates.adl".parse().unwrap();
+        let contents = include_str!("input/duplicates.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents);
+        assert!(parsed.is_some());
+        assert_yaml_snapshot!(parsed.unwrap().collect_diagnostics(contents));
+    }
+
+    #[test]
+    fn test_collect_field_errors() {
+        let url: Url = "file://foo/fields.adl".parse().unwrap();
+        let contents = include_str!("input/fields.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents);
+        assert!(parsed.is_some());
+        assert_yaml_snapshot!(parsed.unwrap().collect_diagnostics(contents));
+    }
+
+    #[test]
+    fn test_collect_primitive_arity_errors() {
+        let url: Url = "file://foo/arity.adl".parse().unwrap();
+        let contents = include_str!("input/arity.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents);
+        assert!(parsed.is_some());
+        assert_yaml_snapshot!(parsed.unwrap().collect_primitive_arity_diagnostics());
+    }
+
+    #[test]
+    fn test_collect_unused_import_warnings() {
+        let url: Url = "file://foo/unusedimports.adl".parse().unwrap();
+        let contents = include_str!("input/unusedimports.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents);
+        assert!(parsed.is_some());
+        assert_yaml_snapshot!(parsed.unwrap().collect_diagnostics(contents));
+    }
 }
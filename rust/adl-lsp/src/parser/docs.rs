@@ -0,0 +1,152 @@
+use std::collections::HashSet;
+
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range};
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::node::NodeKind;
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+
+/// A type, field or union variant that should be documented
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentableItem {
+    /// `type`, `field` or `variant`
+    pub kind: &'static str,
+    /// The name of a type, or `Type::field` for a field or variant
+    pub name: String,
+    pub range: Range,
+    pub documented: bool,
+}
+
+impl ParsedTree {
+    /// Every type, field and union variant defined by the module, and whether it is documented by a
+    /// `///` docstring, a `@Doc` annotation or an `annotation Type::field Doc "..."` declaration
+    pub fn find_documentable_items(&self, content: &[u8]) -> Vec<DocumentableItem> {
+        let declared_docs = self.find_declared_docs(content);
+        let mut items = vec![];
+
+        for type_name in self.find_all_nodes(NodeKind::is_type_name) {
+            let (Some(definition), Ok(name)) = (type_name.parent(), type_name.utf8_text(content))
+            else {
+                continue;
+            };
+            items.push(DocumentableItem {
+                kind: "type",
+                name: name.to_string(),
+                range: ts_lsp_interop::ts_to_lsp_range(&type_name.range()),
+                documented: Self::has_doc_preamble(&definition, content)
+                    || declared_docs.contains(name),
+            });
+        }
+
+        for field in self.find_all_nodes(NodeKind::is_field) {
+            let Some(name_node) = Self::field_name_node(&field) else {
+                continue;
+            };
+            let (Some(definition), Ok(field_name)) = (
+                Self::find_enclosing_definition(&field),
+                name_node.utf8_text(content),
+            ) else {
+                continue;
+            };
+            let Some(type_name) = Self::definition_name(&definition, content) else {
+                continue;
+            };
+            let name = format!("{}::{}", type_name, field_name);
+            items.push(DocumentableItem {
+                kind: if NodeKind::is_union_definition(&definition) {
+                    "variant"
+                } else {
+                    "field"
+                },
+                range: ts_lsp_interop::ts_to_lsp_range(&name_node.range()),
+                documented: Self::has_doc_preamble(&field, content)
+                    || declared_docs.contains(&name),
+                name,
+            });
+        }
+
+        items.sort_by_key(|item| (item.range.start.line, item.range.start.character));
+        items
+    }
+
+    /// Report types, fields and variants that have no documentation
+    pub fn collect_missing_doc_diagnostics(&self, content: &[u8]) -> Vec<Diagnostic> {
+        self.find_documentable_items(content)
+            .into_iter()
+            .filter(|item| !item.documented)
+            .map(|item| Diagnostic {
+                range: item.range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(String::from("missing-doc"))),
+                message: format!(
+                    "{} `{}` has no `///` docstring or `@Doc` annotation",
+                    item.kind, item.name
+                ),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// The types and fields documented by `annotation Type Doc "..."` or
+    /// `annotation Type::field Doc "..."` declarations, as `Type` or `Type::field`
+    fn find_declared_docs(&self, content: &[u8]) -> HashSet<String> {
+        self.find_all_nodes(NodeKind::is_annotation_declaration)
+            .into_iter()
+            .filter_map(|declaration| {
+                let (annotation_type, _) = Self::annotation_type_and_value(&declaration)?;
+                if !Self::is_doc(&annotation_type, content) {
+                    return None;
+                }
+
+                match self.annotation_declaration_target(&declaration, content)? {
+                    (type_name, Some(field_name)) => Some(format!("{}::{}", type_name, field_name)),
+                    (type_name, None) => Some(type_name.to_string()),
+                }
+            })
+            .collect()
+    }
+
+    /// Check whether a definition or field is preceded by a docstring or a `@Doc` annotation
+    fn has_doc_preamble(node: &Node, content: &[u8]) -> bool {
+        let Some(preamble) = node
+            .children(&mut node.walk())
+            .find(NodeKind::is_definition_preamble)
+        else {
+            return false;
+        };
+        preamble.named_children(&mut preamble.walk()).any(|n| {
+            NodeKind::is_docstring(&n)
+                || (NodeKind::is_annotation_decorator(&n)
+                    && Self::annotation_type_and_value(&n).is_some_and(|(annotation_type, _)| {
+                        Self::is_doc(&annotation_type, content)
+                    }))
+        })
+    }
+
+    /// Check whether an annotation is `Doc`, whether or not it is qualified by `sys.annotations`
+    fn is_doc(annotation_type: &Node, content: &[u8]) -> bool {
+        annotation_type
+            .utf8_text(content)
+            .is_ok_and(|name| name == "Doc" || name.ends_with(".Doc"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::Url;
+    use insta::assert_yaml_snapshot;
+
+    use crate::parser::AdlParser;
+
+    #[test]
+    fn test_find_documentable_items() {
+        let url: Url = "file://foo/docs.adl".parse().unwrap();
+        let contents = include_str!("input/docs.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents).unwrap();
+        assert_yaml_snapshot!(parsed.find_documentable_items(contents.as_bytes()));
+    }
+}
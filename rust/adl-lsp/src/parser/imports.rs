@@ -0,0 +1,673 @@
+use std::collections::{HashMap, HashSet};
+
+use async_lsp::lsp_types::{Position, Range, TextEdit};
+use tree_sitter::{Node, Point};
+
+use crate::node::{AdlImportDeclaration, NodeKind};
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop::{self, ts_to_lsp_position};
+
+const DEFAULT_INDENT: &str = "    ";
+
+impl ParsedTree {
+    /// Check whether the document already imports a type, either explicitly or through a star import
+    pub fn has_import(&self, module_name: &str, type_name: &str, content: &[u8]) -> bool {
+        self.find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .any(|import| {
+                import.module_name(content) == module_name
+                    && import
+                        .imported_type_name(content)
+                        .is_none_or(|imported| imported == type_name)
+            })
+    }
+
+    /// The names referenced without a module qualifier, which must be in scope through a definition or an import
+    pub fn find_unqualified_references<'c>(&self, content: &'c [u8]) -> HashSet<&'c str> {
+        self.find_all_nodes(NodeKind::is_scoped_name)
+            .into_iter()
+            .filter(|n| {
+                n.parent().is_some_and(|p| {
+                    !NodeKind::is_module_definition(&p) && !NodeKind::is_import_path(&p)
+                })
+            })
+            .filter(|n| n.named_child_count() == 1)
+            .filter_map(|n| {
+                let name = n.utf8_text(content).ok()?;
+                (!Self::is_type_parameter_reference(&n, name, content)).then_some(name)
+            })
+            .collect()
+    }
+
+    /// Create an edit that removes an import declaration, along with its line when nothing else is on it
+    pub fn import_removal_edit(&self, position: &Position, content: &[u8]) -> Option<TextEdit> {
+        let import = self.find_import_at(position)?;
+        let (range, _) = Self::import_removal_range(&import, content);
+
+        Some(TextEdit {
+            range,
+            new_text: String::new(),
+        })
+    }
+
+    /// Create the edits that sort the imports of the module, move misplaced imports to the top of
+    /// the module body and drop duplicate and unused ones.
+    ///
+    /// `star_import_types` maps modules to the types that they define, which decides whether a star
+    /// import is used. Star imports of modules that are missing from the map are always kept.
+    pub fn organize_imports_edits(
+        &self,
+        star_import_types: &HashMap<String, Vec<String>>,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let import_nodes = self.find_all_nodes(NodeKind::is_import_declaration);
+        let imports: Vec<AdlImportDeclaration> = import_nodes
+            .iter()
+            .filter_map(|n| AdlImportDeclaration::try_new(*n))
+            .collect();
+        if imports.is_empty() {
+            return vec![];
+        }
+
+        let references = self.find_unqualified_references(content);
+        let local_type_names = self.find_local_type_names(content);
+        let explicitly_imported_names: Vec<&str> = imports
+            .iter()
+            .filter_map(|import| import.imported_type_name(content))
+            .collect();
+
+        let current_paths: Vec<String> = imports
+            .iter()
+            .map(|import| Self::import_path_text(import, content))
+            .collect();
+        let mut organized_paths: Vec<String> = imports
+            .iter()
+            .filter(|import| match import.imported_type_name(content) {
+                Some(type_name) => references.contains(type_name),
+                None => star_import_types
+                    .get(import.module_name(content))
+                    .is_none_or(|type_names| {
+                        type_names.is_empty()
+                            || type_names.iter().any(|name| {
+                                references.contains(name.as_str())
+                                    && !local_type_names.contains(&name.as_str())
+                                    && !explicitly_imported_names.contains(&name.as_str())
+                            })
+                    }),
+            })
+            .map(|import| Self::import_path_text(import, content))
+            .collect();
+        organized_paths.sort();
+        organized_paths.dedup();
+
+        let leading_imports = self.find_leading_imports();
+        if organized_paths == current_paths && leading_imports.len() == import_nodes.len() {
+            return vec![];
+        }
+
+        let anchor = leading_imports.first();
+        let indent = anchor
+            .map(|n| Self::indentation_of(&n.start_position(), content))
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| DEFAULT_INDENT.to_string());
+
+        let mut edits = vec![];
+        for node in &import_nodes {
+            let (range, is_alone_on_line) = Self::import_removal_range(node, content);
+            if anchor.is_some_and(|anchor| anchor.id() == node.id()) {
+                // the organized imports replace the first import at the top of the module
+                let new_text = if is_alone_on_line {
+                    organized_paths
+                        .iter()
+                        .map(|path| format!("{}import {};\n", indent, path))
+                        .collect::<Vec<_>>()
+                        .concat()
+                } else {
+                    organized_paths
+                        .iter()
+                        .map(|path| format!("import {};", path))
+                        .collect::<Vec<_>>()
+                        .join(&format!("\n{}", indent))
+                };
+                let range = if is_alone_on_line {
+                    range
+                } else {
+                    ts_lsp_interop::ts_to_lsp_range(&node.range())
+                };
+                edits.push(TextEdit { range, new_text });
+            } else {
+                let range = if is_alone_on_line {
+                    Self::with_preceding_blank_line(range, content)
+                } else {
+                    range
+                };
+                edits.push(TextEdit {
+                    range,
+                    new_text: String::new(),
+                });
+            }
+        }
+
+        if anchor.is_none() && !organized_paths.is_empty() {
+            let opening_brace = self
+                .find_first_node(NodeKind::is_module_body)
+                .and_then(|n| n.child(0))
+                .filter(|n| n.kind() == "{");
+            if let Some(opening_brace) = opening_brace {
+                let position = ts_to_lsp_position(&opening_brace.end_position());
+                let new_text = organized_paths
+                    .iter()
+                    .map(|path| format!("\n{}import {};", indent, path))
+                    .collect::<Vec<_>>()
+                    .concat()
+                    + "\n";
+                edits.push(TextEdit {
+                    range: Range::new(position, position),
+                    new_text,
+                });
+            }
+        }
+
+        edits
+    }
+
+    /// Create an edit that replaces the star import at a position with explicit imports of the
+    /// types it provides that are used in the document, returning the imported module too
+    ///
+    /// `star_import_types` maps modules to the types that they define.
+    pub fn expand_star_import_edit(
+        &self,
+        position: &Position,
+        star_import_types: &HashMap<String, Vec<String>>,
+        content: &[u8],
+    ) -> Option<(String, TextEdit)> {
+        let node = self.find_import_at(position)?;
+        let import = AdlImportDeclaration::try_new(node)?;
+        if import.imported_type_name(content).is_some() {
+            return None;
+        }
+        let module_name = import.module_name(content);
+        let type_names = star_import_types.get(module_name)?;
+
+        let references = self.find_unqualified_references(content);
+        let local_type_names = self.find_local_type_names(content);
+        let mut used_names: Vec<&String> = type_names
+            .iter()
+            .filter(|name| {
+                references.contains(name.as_str())
+                    && !local_type_names.contains(&name.as_str())
+                    && !self.has_explicit_import(module_name, name, content)
+            })
+            .collect();
+        if used_names.is_empty() {
+            return None;
+        }
+        used_names.sort();
+        used_names.dedup();
+
+        let indent = Self::indentation_of(&node.start_position(), content);
+        let new_text = used_names
+            .iter()
+            .map(|name| format!("import {}.{};", module_name, name))
+            .collect::<Vec<_>>()
+            .join(&format!("\n{}", indent));
+
+        Some((
+            module_name.to_string(),
+            TextEdit {
+                range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+                new_text,
+            },
+        ))
+    }
+
+    /// Create the edits that replace every explicit import from the module imported at a position
+    /// with a single star import, returning the imported module too
+    pub fn collapse_imports_edits(
+        &self,
+        position: &Position,
+        content: &[u8],
+    ) -> Option<(String, Vec<TextEdit>)> {
+        let import = AdlImportDeclaration::try_new(self.find_import_at(position)?)?;
+        import.imported_type_name(content)?;
+        let module_name = import.module_name(content);
+
+        let module_imports: Vec<AdlImportDeclaration> = self
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .filter(|import| import.module_name(content) == module_name)
+            .collect();
+        let has_star_import = module_imports
+            .iter()
+            .any(|import| import.imported_type_name(content).is_none());
+        if has_star_import || module_imports.len() < 2 {
+            return None;
+        }
+
+        let edits = module_imports
+            .iter()
+            .enumerate()
+            .map(|(i, import)| {
+                if i == 0 {
+                    TextEdit {
+                        range: ts_lsp_interop::ts_to_lsp_range(&import.inner().range()),
+                        new_text: format!("import {}.*;", module_name),
+                    }
+                } else {
+                    TextEdit {
+                        range: Self::import_removal_range(import.inner(), content).0,
+                        new_text: String::new(),
+                    }
+                }
+            })
+            .collect();
+
+        Some((module_name.to_string(), edits))
+    }
+
+    fn has_explicit_import(&self, module_name: &str, type_name: &str, content: &[u8]) -> bool {
+        self.find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .any(|import| {
+                import.module_name(content) == module_name
+                    && import.imported_type_name(content) == Some(type_name)
+            })
+    }
+
+    /// The imports at the top of the module body, before any definition
+    fn find_leading_imports(&self) -> Vec<Node<'_>> {
+        let Some(module_body) = self.find_first_node(NodeKind::is_module_body) else {
+            return vec![];
+        };
+        let mut cursor = module_body.walk();
+        module_body
+            .children(&mut cursor)
+            .skip(1) // opening module brace
+            .filter(|n| !NodeKind::is_comment(n) && !NodeKind::is_docstring(n))
+            .take_while(NodeKind::is_import_declaration)
+            .collect()
+    }
+
+    fn find_import_at(&self, position: &Position) -> Option<Node<'_>> {
+        let mut import = self.get_node_at_position(position)?;
+        while !NodeKind::is_import_declaration(&import) {
+            import = import.parent()?;
+        }
+        Some(import)
+    }
+
+    /// The path of an import as written, without extra whitespace e.g. `common.db.User` or `common.db.*`
+    fn import_path_text(import: &AdlImportDeclaration, content: &[u8]) -> String {
+        match import.imported_type_name(content) {
+            Some(type_name) => format!("{}.{}", import.module_name(content), type_name),
+            None => format!("{}.*", import.module_name(content)),
+        }
+    }
+
+    /// The range to delete to remove an import, which covers its whole line when nothing else is on it
+    fn import_removal_range(import: &Node<'_>, content: &[u8]) -> (Range, bool) {
+        let start = import.start_position();
+        let end = import.end_position();
+        let text = String::from_utf8_lossy(content);
+        let lines: Vec<&str> = text.lines().collect();
+        let is_alone_on_line = lines.get(start.row).is_some_and(|line| {
+            line.get(..start.column)
+                .is_some_and(|s| s.trim().is_empty())
+        }) && lines
+            .get(end.row)
+            .is_some_and(|line| line.get(end.column..).is_some_and(|s| s.trim().is_empty()));
+
+        if is_alone_on_line {
+            (
+                Range::new(
+                    Position::new(start.row as u32, 0),
+                    Position::new(end.row as u32 + 1, 0),
+                ),
+                true,
+            )
+        } else {
+            (
+                Range::new(ts_to_lsp_position(&start), ts_to_lsp_position(&end)),
+                false,
+            )
+        }
+    }
+
+    /// Extend a range of whole lines over the blank line before it, when removing the lines would
+    /// otherwise leave that blank line before another one or before the end of a block
+    fn with_preceding_blank_line(range: Range, content: &[u8]) -> Range {
+        let text = String::from_utf8_lossy(content);
+        let lines: Vec<&str> = text.lines().collect();
+        let start = range.start.line as usize;
+        let preceding = start.checked_sub(1).and_then(|line| lines.get(line));
+        let following = lines.get(range.end.line as usize);
+
+        let is_preceded_by_blank_line = preceding.is_some_and(|line| line.trim().is_empty());
+        let is_followed_by_blank_line_or_end = following
+            .is_some_and(|line| line.trim().is_empty() || line.trim_start().starts_with('}'));
+        if is_preceded_by_blank_line && is_followed_by_blank_line_or_end {
+            Range::new(Position::new(start as u32 - 1, 0), range.end)
+        } else {
+            range
+        }
+    }
+
+    /// Check whether the document imports from a module or refers to one of its types by a fully qualified name
+    pub fn references_module(&self, module_name: &str, content: &[u8]) -> bool {
+        let imports_module = self
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .any(|import| import.module_name(content) == module_name);
+
+        imports_module
+            || self
+                .find_all_nodes(NodeKind::is_scoped_name)
+                .into_iter()
+                .filter(|n| {
+                    n.parent()
+                        .is_some_and(|p| !NodeKind::is_module_definition(&p))
+                })
+                .filter_map(|n| n.utf8_text(content).ok())
+                .filter_map(|name| name.rsplit_once('.'))
+                .any(|(qualifier, _)| qualifier == module_name)
+    }
+
+    /// Create an edit that inserts `import <module_name>.<type_name>;` into the module body.
+    ///
+    /// The import is placed after the imports at the top of the module body, before the first
+    /// definition, so that it is never reported as a misplaced import.
+    pub fn import_insertion_edit(
+        &self,
+        module_name: &str,
+        type_name: &str,
+        content: &[u8],
+    ) -> Option<TextEdit> {
+        let module_body = self.find_first_node(NodeKind::is_module_body)?;
+        let mut cursor = module_body.walk();
+        let children: Vec<_> = module_body.children(&mut cursor).collect();
+        let opening_brace = children.first().filter(|n| n.kind() == "{")?;
+
+        let mut last_leading_import = None;
+        let mut first_member = None;
+        for child in children.iter().skip(1) {
+            if NodeKind::is_import_declaration(child) {
+                last_leading_import = Some(*child);
+            } else if !NodeKind::is_comment(child) && child.kind() != "}" {
+                first_member = Some(*child);
+                break;
+            }
+        }
+
+        let import_line = format!("import {}.{};", module_name, type_name);
+
+        let edit = match last_leading_import {
+            Some(import) => {
+                let indent = Self::indentation_of(&import.start_position(), content);
+                let position = ts_to_lsp_position(&import.end_position());
+                TextEdit {
+                    range: Range::new(position, position),
+                    new_text: format!("\n{}{}", indent, import_line),
+                }
+            }
+            None => {
+                let indent = first_member
+                    .map(|n| Self::indentation_of(&n.start_position(), content))
+                    .filter(|indent| !indent.is_empty())
+                    .unwrap_or_else(|| DEFAULT_INDENT.to_string());
+                let position = ts_to_lsp_position(&opening_brace.end_position());
+                TextEdit {
+                    range: Range::new(position, position),
+                    new_text: format!("\n{}{}\n", indent, import_line),
+                }
+            }
+        };
+
+        Some(edit)
+    }
+
+    /// The whitespace that precedes a position on its line
+    fn indentation_of(point: &Point, content: &[u8]) -> String {
+        String::from_utf8_lossy(content)
+            .lines()
+            .nth(point.row)
+            .unwrap_or_default()
+            .chars()
+            .take(point.column)
+            .take_while(|c| c.is_whitespace())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use async_lsp::lsp_types::{Position, Range, TextEdit, Url};
+
+    use crate::parser::AdlParser;
+
+    #[test]
+    fn test_import_insertion_after_imports() {
+        let contents = r#"module input.message {
+  import common.db.User;
+
+  struct Message {
+    User user;
+  };
+
+  import common.db.Misplaced;
+};
+"#;
+        let uri: Url = "file://input/message.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, contents).unwrap();
+
+        assert!(tree.has_import("common.db", "User", contents.as_bytes()));
+        assert!(tree.references_module("common.db", contents.as_bytes()));
+        assert!(!tree.references_module("common", contents.as_bytes()));
+        assert!(!tree.has_import("common.strings", "StringNE", contents.as_bytes()));
+
+        let edit = tree
+            .import_insertion_edit("common.strings", "StringNE", contents.as_bytes())
+            .unwrap();
+        assert_eq!(
+            edit,
+            TextEdit {
+                range: Range::new(Position::new(1, 24), Position::new(1, 24)),
+                new_text: "\n  import common.strings.StringNE;".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_insertion_without_imports() {
+        let contents = r#"module input.message {
+    /// A message
+    struct Message {
+        String body;
+    };
+};
+"#;
+        let uri: Url = "file://input/message.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, contents).unwrap();
+
+        assert!(!tree.has_import("common.db", "User", contents.as_bytes()));
+
+        let edit = tree
+            .import_insertion_edit("common.db", "User", contents.as_bytes())
+            .unwrap();
+        assert_eq!(
+            edit,
+            TextEdit {
+                range: Range::new(Position::new(0, 22), Position::new(0, 22)),
+                new_text: "\n    import common.db.User;\n".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_import_removal_edit() {
+        let contents = r#"module input.message {
+    import common.db.User;
+    import common.db.Role; import common.db.Id;
+};
+"#;
+        let uri: Url = "file://input/message.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, contents).unwrap();
+
+        // the whole line is removed when the import is alone on it
+        let edit = tree
+            .import_removal_edit(&Position::new(1, 4), contents.as_bytes())
+            .unwrap();
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(1, 0), Position::new(2, 0))
+        );
+
+        // otherwise only the import itself is removed
+        let edit = tree
+            .import_removal_edit(&Position::new(2, 30), contents.as_bytes())
+            .unwrap();
+        assert_eq!(
+            edit.range,
+            Range::new(Position::new(2, 27), Position::new(2, 47))
+        );
+    }
+
+    /// Apply non-overlapping edits to a document, from the last to the first
+    fn apply_edits(contents: &str, edits: &[TextEdit]) -> String {
+        let offset = |position: &Position| {
+            contents
+                .split_inclusive('\n')
+                .take(position.line as usize)
+                .map(str::len)
+                .sum::<usize>()
+                + position.character as usize
+        };
+
+        let mut edits = edits.to_vec();
+        edits.sort_by_key(|edit| std::cmp::Reverse(offset(&edit.range.start)));
+        let mut result = contents.to_string();
+        for edit in edits {
+            result.replace_range(
+                offset(&edit.range.start)..offset(&edit.range.end),
+                &edit.new_text,
+            );
+        }
+        result
+    }
+
+    #[test]
+    fn test_organize_imports() {
+        let contents = r#"module input.message {
+    import common.strings.*;
+    import common.db.User;
+    import common.db.Role;
+    import common.db.User;
+    import common.time.*;
+
+    struct Message {
+        User author;
+        StringNE body;
+        Id id;
+    };
+
+    import common.db.Id;
+};
+"#;
+        let uri: Url = "file://input/message.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, contents).unwrap();
+
+        let star_import_types = HashMap::from([
+            (
+                String::from("common.strings"),
+                vec![String::from("StringNE")],
+            ),
+            (String::from("common.time"), vec![String::from("Instant")]),
+        ]);
+        let edits = tree.organize_imports_edits(&star_import_types, contents.as_bytes());
+        let organized = apply_edits(contents, &edits);
+        assert_eq!(
+            organized,
+            r#"module input.message {
+    import common.db.Id;
+    import common.db.User;
+    import common.strings.*;
+
+    struct Message {
+        User author;
+        StringNE body;
+        Id id;
+    };
+};
+"#
+        );
+
+        // organizing again changes nothing
+        let tree = AdlParser::new()
+            .parse("file://input/message.adl".parse().unwrap(), &organized)
+            .unwrap();
+        assert!(
+            tree.organize_imports_edits(&star_import_types, organized.as_bytes())
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_convert_star_imports() {
+        let contents = r#"module input.message {
+    import common.db.*;
+    import common.strings.StringNE;
+    import common.strings.StringML;
+
+    struct Message {
+        User author;
+        Role role;
+        StringNE body;
+        StringML footer;
+    };
+};
+"#;
+        let uri: Url = "file://input/message.adl".parse().unwrap();
+        let tree = AdlParser::new().parse(uri, contents).unwrap();
+
+        let star_import_types = HashMap::from([(
+            String::from("common.db"),
+            vec![
+                String::from("User"),
+                String::from("Role"),
+                String::from("Id"),
+            ],
+        )]);
+        let (module_name, edit) = tree
+            .expand_star_import_edit(
+                &Position::new(1, 12),
+                &star_import_types,
+                contents.as_bytes(),
+            )
+            .unwrap();
+        assert_eq!(module_name, "common.db");
+        assert_eq!(
+            edit.new_text,
+            "import common.db.Role;\n    import common.db.User;"
+        );
+
+        let (module_name, edits) = tree
+            .collapse_imports_edits(&Position::new(3, 12), contents.as_bytes())
+            .unwrap();
+        assert_eq!(module_name, "common.strings");
+        assert!(apply_edits(contents, &edits).contains(
+            "    import common.db.*;\n    import common.strings.*;\n\n    struct Message {"
+        ));
+
+        // a single explicit import is not collapsed
+        assert!(
+            tree.collapse_imports_edits(&Position::new(1, 12), contents.as_bytes())
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::collections::HashMap;
+
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use serde::Deserialize;
+use tracing::warn;
+
+/// A check that reports diagnostics under a stable code, which users can reconfigure or suppress
+#[derive(Debug, Clone, Copy)]
+pub struct LintRule {
+    pub code: &'static str,
+    pub default_level: LintLevel,
+    pub description: &'static str,
+}
+
+/// Every rule the server reports diagnostics for
+pub const LINT_RULES: &[LintRule] = &[
+    LintRule {
+        code: "unresolved-import",
+        default_level: LintLevel::Error,
+        description: "an import names a module or type that cannot be found",
+    },
+    LintRule {
+        code: "unknown-type",
+        default_level: LintLevel::Error,
+        description: "a type expression names a type that is not in scope",
+    },
+    LintRule {
+        code: "unused-import",
+        default_level: LintLevel::Warning,
+        description: "an import is never used",
+    },
+    LintRule {
+        code: "duplicate-import",
+        default_level: LintLevel::Warning,
+        description: "a type is imported more than once",
+    },
+    LintRule {
+        code: "ambiguous-type",
+        default_level: LintLevel::Error,
+        description: "an unqualified name is brought into scope by more than one star import",
+    },
+    LintRule {
+        code: "ambiguous-import",
+        default_level: LintLevel::Error,
+        description: "a star import brings in a name that another star import also brings in",
+    },
+    LintRule {
+        code: "duplicate-definition",
+        default_level: LintLevel::Error,
+        description: "a type or module is defined more than once",
+    },
+    LintRule {
+        code: "duplicate-field",
+        default_level: LintLevel::Error,
+        description: "a struct or union has two fields with the same name",
+    },
+    LintRule {
+        code: "serialized-name-collision",
+        default_level: LintLevel::Error,
+        description: "two fields of a struct or union are serialized under the same name",
+    },
+    LintRule {
+        code: "type-arity",
+        default_level: LintLevel::Error,
+        description: "a generic type is given the wrong number of type arguments",
+    },
+    LintRule {
+        code: "invalid-default",
+        default_level: LintLevel::Error,
+        description: "a default value does not conform to its type",
+    },
+    LintRule {
+        code: "invalid-annotation-value",
+        default_level: LintLevel::Error,
+        description: "an annotation value does not conform to the annotation type",
+    },
+    LintRule {
+        code: "module-path-mismatch",
+        default_level: LintLevel::Warning,
+        description: "a module name does not match the location of its file",
+    },
+    LintRule {
+        code: "cyclic-type-alias",
+        default_level: LintLevel::Error,
+        description: "a type alias is defined in terms of itself",
+    },
+    LintRule {
+        code: "unconstructible-type",
+        default_level: LintLevel::Warning,
+        description: "a struct or union can never be constructed",
+    },
+    LintRule {
+        code: "undeclared-dependency",
+        default_level: LintLevel::Error,
+        description: "a module is imported from a package that is not a declared dependency",
+    },
+    LintRule {
+        code: "type-name-case",
+        default_level: LintLevel::Warning,
+        description: "a type is not named in UpperCamelCase",
+    },
+    LintRule {
+        code: "type-parameter-case",
+        default_level: LintLevel::Warning,
+        description: "a type parameter is not a single uppercase letter or UpperCamelCase",
+    },
+    LintRule {
+        code: "field-name-case",
+        default_level: LintLevel::Warning,
+        description: "a field or union variant is not named in lowerCamelCase",
+    },
+    LintRule {
+        code: "module-name-case",
+        default_level: LintLevel::Warning,
+        description: "a module path segment is not lowercase",
+    },
+    LintRule {
+        code: "reserved-word",
+        default_level: LintLevel::Warning,
+        description: "a type, field or variant name is a reserved word in a target language",
+    },
+    LintRule {
+        code: "missing-doc",
+        default_level: LintLevel::Off,
+        description: "a type, field or variant has no `///` docstring or `@Doc` annotation",
+    },
+];
+
+/// How a lint rule is reported, or `off` to not report it at all
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LintLevel {
+    Off,
+    Hint,
+    #[serde(alias = "information")]
+    Info,
+    Warning,
+    Error,
+}
+
+impl LintLevel {
+    fn severity(&self) -> Option<DiagnosticSeverity> {
+        match self {
+            LintLevel::Off => None,
+            LintLevel::Hint => Some(DiagnosticSeverity::HINT),
+            LintLevel::Info => Some(DiagnosticSeverity::INFORMATION),
+            LintLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            LintLevel::Error => Some(DiagnosticSeverity::ERROR),
+        }
+    }
+}
+
+/// The level of each lint rule that a workspace has chosen to override
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(transparent)]
+pub struct LintConfig {
+    overrides: HashMap<String, LintLevel>,
+}
+
+/// Marks a comment that suppresses rules on the following line e.g. `// adl-lsp: allow(unused-import)`
+const SUPPRESSION_MARKER: &str = "adl-lsp: allow(";
+
+impl LintConfig {
+    /// Read the overrides from the `lints` object of the client settings e.g. `{"unused-import": "off"}`,
+    /// ignoring any rule that does not exist
+    pub fn from_settings(settings: &serde_json::Value) -> Result<Self, serde_json::Error> {
+        let config = LintConfig::deserialize(settings)?;
+        for code in config.overrides.keys() {
+            if Self::rule(code).is_none() {
+                warn!("ignoring configuration of unknown lint rule: {}", code);
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn rule(code: &str) -> Option<&'static LintRule> {
+        LINT_RULES.iter().find(|rule| rule.code == code)
+    }
+
+    /// The level that a rule is reported at in this workspace
+    pub fn level(&self, rule: &LintRule) -> LintLevel {
+        self.overrides
+            .get(rule.code)
+            .copied()
+            .unwrap_or(rule.default_level)
+    }
+
+    /// Set the severity of each diagnostic from its rule, dropping those whose rule is turned off or
+    /// suppressed by a comment on the preceding line. Diagnostics without a rule (e.g. syntax errors)
+    /// are always reported
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>, content: &str) -> Vec<Diagnostic> {
+        let lines: Vec<&str> = content.lines().collect();
+
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                    return Some(diagnostic);
+                };
+                let Some(rule) = Self::rule(code) else {
+                    return Some(diagnostic);
+                };
+
+                let line = diagnostic.range.start.line as usize;
+                let is_suppressed = line
+                    .checked_sub(1)
+                    .and_then(|preceding| lines.get(preceding))
+                    .is_some_and(|preceding| {
+                        Self::suppressed_codes(preceding).contains(&rule.code)
+                    });
+                if is_suppressed {
+                    return None;
+                }
+
+                diagnostic.severity = Some(self.level(rule).severity()?);
+                Some(diagnostic)
+            })
+            .collect()
+    }
+
+    /// The rules allowed by a suppression comment on a line
+    fn suppressed_codes(line: &str) -> Vec<&str> {
+        let Some(comment) = line.trim_start().strip_prefix("//") else {
+            return vec![];
+        };
+        let Some(allowed) = comment.trim_start().strip_prefix(SUPPRESSION_MARKER) else {
+            return vec![];
+        };
+        let Some((allowed, _)) = allowed.split_once(')') else {
+            return vec![];
+        };
+        allowed.split(',').map(str::trim).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range};
+    use serde_json::json;
+
+    use super::*;
+
+    fn diagnostic(line: u32, code: Option<&str>) -> Diagnostic {
+        Diagnostic {
+            range: Range::new(Position::new(line, 2), Position::new(line, 8)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: code.map(|code| NumberOrString::String(code.to_string())),
+            message: String::from("message"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_lint_levels() {
+        let config = LintConfig::from_settings(&json!({
+            "unused-import": "off",
+            "unconstructible-type": "error",
+            "unknown-type": "hint",
+        }))
+        .unwrap();
+
+        let diagnostics = config.apply(
+            vec![
+                diagnostic(0, Some("unused-import")),
+                diagnostic(1, Some("unconstructible-type")),
+                diagnostic(2, Some("unknown-type")),
+                diagnostic(3, Some("duplicate-import")),
+                diagnostic(4, None),
+            ],
+            "",
+        );
+        let levels: Vec<(u32, Option<DiagnosticSeverity>)> = diagnostics
+            .iter()
+            .map(|d| (d.range.start.line, d.severity))
+            .collect();
+        assert_eq!(
+            levels,
+            vec![
+                (1, Some(DiagnosticSeverity::ERROR)),
+                (2, Some(DiagnosticSeverity::HINT)),
+                (3, Some(DiagnosticSeverity::WARNING)),
+                (4, Some(DiagnosticSeverity::ERROR)),
+            ]
+        );
+
+        assert!(LintConfig::from_settings(&json!({ "unused-import": "loud" })).is_err());
+    }
+
+    #[test]
+    fn test_suppression_comments() {
+        let content = r#"module input.suppressed {
+  // adl-lsp: allow(unused-import)
+  import common.Unused;
+  // adl-lsp: allow(duplicate-import, unknown-type)
+  import common.Twice;
+  import common.Reported;
+  // only a comment that starts with adl-lsp: allow(unknown-type) counts
+  Missing field;
+};
+"#;
+
+        let diagnostics = LintConfig::default().apply(
+            vec![
+                diagnostic(2, Some("unused-import")),
+                diagnostic(4, Some("duplicate-import")),
+                diagnostic(4, Some("unresolved-import")),
+                diagnostic(5, Some("unused-import")),
+                diagnostic(7, Some("unknown-type")),
+            ],
+            content,
+        );
+        let reported: Vec<(u32, String)> = diagnostics
+            .into_iter()
+            .filter_map(|d| match d.code {
+                Some(NumberOrString::String(code)) => Some((d.range.start.line, code)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            reported,
+            vec![
+                (4, String::from("unresolved-import")),
+                (5, String::from("unused-import")),
+                (7, String::from("unknown-type")),
+            ]
+        );
+    }
+}
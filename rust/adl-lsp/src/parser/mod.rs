@@ -1,16 +1,23 @@
 use async_lsp::lsp_types::Url;
 use std::sync::Arc;
-use tree_sitter::Tree as TsTree;
+use tree_sitter::{Node, Tree as TsTree};
 
 use crate::{
     node::{AdlModuleDefinition, NodeKind},
     parser::tree::Tree,
 };
 
+pub mod completion;
 pub mod definition;
 pub mod diagnostics;
+pub mod docs;
 pub mod hover;
+pub mod imports;
+pub mod lints;
+pub mod naming;
 pub mod references;
+pub mod rename;
+pub mod reserved;
 pub mod symbols;
 pub mod tree;
 pub mod ts_lsp_interop;
@@ -59,4 +66,117 @@ impl ParsedTree {
         let module_body_node = AdlModuleDefinition::try_new(module_body_node)?;
         Some(module_body_node.module_name(content))
     }
+
+    /// The names of all types defined by this document
+    pub fn find_local_type_names<'c>(&self, content: &'c [u8]) -> Vec<&'c str> {
+        self.find_all_nodes(NodeKind::is_local_definition)
+            .into_iter()
+            .filter_map(|n| Self::definition_name(&n, content))
+            .collect()
+    }
+
+    /// The names of the fields of a struct or union defined by this document
+    pub fn find_field_names<'c>(&self, type_name: &str, content: &'c [u8]) -> Vec<&'c str> {
+        self.find_all_nodes(NodeKind::is_field)
+            .into_iter()
+            .filter(|n| {
+                Self::find_enclosing_definition(n).and_then(|d| Self::definition_name(&d, content))
+                    == Some(type_name)
+            })
+            .filter_map(|n| Self::field_name_node(&n))
+            .filter_map(|n| n.utf8_text(content).ok())
+            .collect()
+    }
+
+    /// Find the type, newtype, struct or union definition that encloses a node
+    pub fn find_enclosing_definition<'a>(node: &Node<'a>) -> Option<Node<'a>> {
+        if NodeKind::is_local_definition(node) {
+            Some(*node)
+        } else {
+            node.parent()
+                .and_then(|p| Self::find_enclosing_definition(&p))
+        }
+    }
+
+    /// The name of a type, newtype, struct or union definition
+    pub fn definition_name<'c>(definition: &Node<'_>, content: &'c [u8]) -> Option<&'c str> {
+        definition
+            .children(&mut definition.walk())
+            .find(NodeKind::is_type_name)
+            .and_then(|type_name| type_name.utf8_text(content).ok())
+    }
+
+    /// The annotation type and value of an `annotation_decorator` or `annotation_declaration` e.g.
+    /// `SerializedName` and `"name"` in `@SerializedName "name"` or `annotation Type::field SerializedName "name"`
+    pub fn annotation_type_and_value<'a>(annotation: &Node<'a>) -> Option<(Node<'a>, Node<'a>)> {
+        let mut cursor = annotation.walk();
+        let scoped_names: Vec<Node> = annotation
+            .named_children(&mut cursor)
+            .filter(NodeKind::is_scoped_name)
+            .collect();
+        let annotation_type = if NodeKind::is_annotation_decorator(annotation) {
+            scoped_names.first()
+        } else {
+            // the first scoped name of a declaration is the annotated type
+            scoped_names.get(1)
+        };
+        let json_value = annotation
+            .named_children(&mut cursor)
+            .find(NodeKind::is_json_value);
+        annotation_type.copied().zip(json_value)
+    }
+
+    /// The type and field names that an `annotation_declaration` of this module targets e.g. `Query`
+    /// and `type` in `annotation Query::type JavaFieldName "type_";`. A type qualified by another
+    /// module is not defined here, so it is not a target
+    pub fn annotation_declaration_target<'a>(
+        &self,
+        declaration: &Node,
+        content: &'a [u8],
+    ) -> Option<(&'a str, Option<&'a str>)> {
+        let target_type = declaration.named_child(0)?.utf8_text(content).ok()?;
+        let type_name = match target_type.rsplit_once('.') {
+            Some((qualifier, type_name)) if Some(qualifier) == self.find_module_name(content) => {
+                type_name
+            }
+            Some(_) => return None,
+            None => target_type,
+        };
+        let field_name = match declaration
+            .named_child(1)
+            .filter(NodeKind::is_field_reference)
+        {
+            Some(field_reference) => Some(field_reference.utf8_text(content).ok()?),
+            None => None,
+        };
+        Some((type_name, field_name))
+    }
+
+    /// The type expression and default value of a field or newtype e.g. `Vector<Int32>` and `[]` in
+    /// `Vector<Int32> ids = [];`
+    pub fn type_expression_and_default<'a>(definition: &Node<'a>) -> Option<(Node<'a>, Node<'a>)> {
+        let mut cursor = definition.walk();
+        let type_expression = definition
+            .children(&mut cursor)
+            .find(NodeKind::is_type_expression);
+        let json_value = definition
+            .children(&mut cursor)
+            .find(NodeKind::is_json_value);
+        type_expression.zip(json_value)
+    }
+
+    /// The names of the type parameters declared by a definition e.g. `T` in `struct Pair<T> {...}`
+    pub fn type_parameter_names<'c>(definition: &Node<'_>, content: &'c [u8]) -> Vec<&'c str> {
+        definition
+            .children(&mut definition.walk())
+            .find(NodeKind::is_type_parameters)
+            .map(|type_parameters| {
+                type_parameters
+                    .children(&mut type_parameters.walk())
+                    .filter(NodeKind::is_identifier)
+                    .filter_map(|n| n.utf8_text(content).ok())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
 }
@@ -0,0 +1,280 @@
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, TextEdit};
+use tree_sitter::Node;
+
+use crate::node::NodeKind;
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+
+impl ParsedTree {
+    /// Report names that do not follow the ADL naming conventions: `UpperCamelCase` types and type
+    /// parameters, `lowerCamelCase` fields and variants, and lowercase module path segments.
+    /// The conventional name is attached as the `name` of the diagnostic data for the rename quick fix
+    pub fn collect_naming_diagnostics(&self, content: &[u8]) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        if let Some(module_name) = self.find_module_name(content) {
+            let module_definition = self.find_first_node(NodeKind::is_module_definition);
+            let segments: Vec<Node> = module_definition
+                .and_then(|module_definition| {
+                    module_definition
+                        .named_children(&mut module_definition.walk())
+                        .find(NodeKind::is_scoped_name)
+                })
+                .map(|scoped_name| {
+                    scoped_name
+                        .named_children(&mut scoped_name.walk())
+                        .filter(NodeKind::is_identifier)
+                        .collect()
+                })
+                .unwrap_or_default();
+            let conventional_module_name = module_name
+                .split('.')
+                .map(snake_case)
+                .collect::<Vec<_>>()
+                .join(".");
+            for segment in segments {
+                let Ok(name) = segment.utf8_text(content) else {
+                    continue;
+                };
+                if name.chars().any(char::is_uppercase) {
+                    diagnostics.push(naming_diagnostic(
+                        &segment,
+                        "module-name-case",
+                        format!("module path segment `{}` should be lowercase", name),
+                        &conventional_module_name,
+                    ));
+                }
+            }
+        }
+
+        for type_name in self.find_all_nodes(NodeKind::is_type_name) {
+            let Ok(name) = type_name.utf8_text(content) else {
+                continue;
+            };
+            if !is_upper_camel_case(name) {
+                diagnostics.push(naming_diagnostic(
+                    &type_name,
+                    "type-name-case",
+                    format!("type `{}` should be named in UpperCamelCase", name),
+                    &upper_camel_case(name),
+                ));
+            }
+        }
+
+        for type_parameters in self.find_all_nodes(NodeKind::is_type_parameters) {
+            for parameter in type_parameters
+                .named_children(&mut type_parameters.walk())
+                .filter(NodeKind::is_identifier)
+            {
+                let Ok(name) = parameter.utf8_text(content) else {
+                    continue;
+                };
+                if !is_upper_camel_case(name) {
+                    diagnostics.push(naming_diagnostic(
+                        &parameter,
+                        "type-parameter-case",
+                        format!(
+                            "type parameter `{}` should be a single uppercase letter or UpperCamelCase",
+                            name
+                        ),
+                        &upper_camel_case(name),
+                    ));
+                }
+            }
+        }
+
+        for field in self.find_all_nodes(NodeKind::is_field) {
+            let Some(name_node) = Self::field_name_node(&field) else {
+                continue;
+            };
+            let Ok(name) = name_node.utf8_text(content) else {
+                continue;
+            };
+            if !is_lower_camel_case(name) {
+                let is_variant = field
+                    .parent()
+                    .and_then(|field_block| field_block.parent())
+                    .is_some_and(|definition| NodeKind::is_union_definition(&definition));
+                diagnostics.push(naming_diagnostic(
+                    &name_node,
+                    "field-name-case",
+                    format!(
+                        "{} `{}` should be named in lowerCamelCase",
+                        if is_variant { "variant" } else { "field" },
+                        name
+                    ),
+                    &lower_camel_case(name),
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Find the edits that rename a type parameter, and its uses within its definition, to `new_name`
+    pub fn type_parameter_rename_edits(
+        &self,
+        parameter: &Node<'_>,
+        new_name: &str,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let (Some(definition), Ok(name)) = (
+            Self::find_enclosing_definition(parameter),
+            parameter.utf8_text(content),
+        ) else {
+            return vec![];
+        };
+        let rename_edit = |node: &Node| TextEdit {
+            range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+            new_text: new_name.to_string(),
+        };
+
+        let mut edits = vec![rename_edit(parameter)];
+        edits.extend(
+            self.find_all_nodes(NodeKind::is_scoped_name)
+                .into_iter()
+                .filter(|scoped_name| {
+                    scoped_name.start_byte() >= definition.start_byte()
+                        && scoped_name.end_byte() <= definition.end_byte()
+                        && scoped_name.named_child_count() == 1
+                        && scoped_name.utf8_text(content) == Ok(name)
+                })
+                .filter_map(|scoped_name| scoped_name.named_child(0))
+                .map(|identifier| rename_edit(&identifier)),
+        );
+        edits
+    }
+}
+
+fn naming_diagnostic(node: &Node, code: &str, message: String, name: &str) -> Diagnostic {
+    Diagnostic {
+        range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(code.to_string())),
+        message,
+        data: Some(serde_json::json!({ "name": name })),
+        ..Default::default()
+    }
+}
+
+fn is_upper_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase()) && !name.contains('_')
+}
+
+fn is_lower_camel_case(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase()) && !name.contains('_')
+}
+
+/// Split a name into words at underscores and at each lowercase letter or digit followed by an
+/// uppercase letter e.g. `http_RequestID` -> `http`, `Request`, `ID`
+fn words(name: &str) -> Vec<String> {
+    let mut words = vec![];
+    for part in name.split('_').filter(|part| !part.is_empty()) {
+        let mut word = String::new();
+        let mut previous: Option<char> = None;
+        for c in part.chars() {
+            if c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_ascii_digit())
+            {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+            previous = Some(c);
+        }
+        words.push(word);
+    }
+    words
+}
+
+/// Capitalize a word, lowercasing the rest of it when it is an all uppercase acronym
+fn capitalize(word: &str) -> String {
+    let rest = if word.chars().any(char::is_lowercase) {
+        word.chars().skip(1).collect::<String>()
+    } else {
+        word.chars().skip(1).collect::<String>().to_lowercase()
+    };
+    word.chars()
+        .take(1)
+        .flat_map(char::to_uppercase)
+        .chain(rest.chars())
+        .collect()
+}
+
+pub fn upper_camel_case(name: &str) -> String {
+    words(name).iter().map(|word| capitalize(word)).collect()
+}
+
+pub fn lower_camel_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .enumerate()
+        .map(|(i, word)| {
+            if i == 0 {
+                word.to_lowercase()
+            } else {
+                capitalize(word)
+            }
+        })
+        .collect()
+}
+
+pub fn snake_case(name: &str) -> String {
+    words(name)
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::Url;
+    use insta::assert_yaml_snapshot;
+
+    use super::*;
+    use crate::parser::AdlParser;
+
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(upper_camel_case("http_request"), "HttpRequest");
+        assert_eq!(upper_camel_case("userID"), "UserId");
+        assert_eq!(upper_camel_case("MAX_SIZE"), "MaxSize");
+        assert_eq!(lower_camel_case("First_Name"), "firstName");
+        assert_eq!(lower_camel_case("UserId"), "userId");
+        assert_eq!(lower_camel_case("created_at_2"), "createdAt2");
+        assert_eq!(snake_case("HttpClient"), "http_client");
+        assert_eq!(snake_case("v2Api"), "v2_api");
+    }
+
+    #[test]
+    fn test_collect_naming_diagnostics() {
+        let url: Url = "file://foo/naming.adl".parse().unwrap();
+        let contents = include_str!("input/naming.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents).unwrap();
+        assert_yaml_snapshot!(parsed.collect_naming_diagnostics(contents.as_bytes()));
+    }
+
+    #[test]
+    fn test_type_parameter_rename_edits() {
+        let url: Url = "file://foo/naming.adl".parse().unwrap();
+        let contents = include_str!("input/naming.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents).unwrap();
+        let parameter = parsed
+            .find_all_nodes(NodeKind::is_type_parameters)
+            .into_iter()
+            .flat_map(|type_parameters| {
+                type_parameters
+                    .named_children(&mut type_parameters.walk())
+                    .collect::<Vec<_>>()
+            })
+            .find(|n| n.utf8_text(contents.as_bytes()) == Ok("key_type"))
+            .unwrap();
+        assert_yaml_snapshot!(parsed.type_parameter_rename_edits(
+            &parameter,
+            "KeyType",
+            contents.as_bytes()
+        ));
+    }
+}
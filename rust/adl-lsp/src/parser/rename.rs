@@ -0,0 +1,305 @@
+use async_lsp::lsp_types::{Range, TextEdit};
+use tree_sitter::Node;
+
+use crate::node::NodeKind;
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+
+impl ParsedTree {
+    /// Find the edits that rename the type `module_name.type_name` to `new_name` in this document.
+    ///
+    /// This covers the definition itself, import declarations, fully qualified references and any
+    /// unqualified references when the type is in scope (i.e. it is defined by the same module or imported).
+    pub fn type_rename_edits(
+        &self,
+        module_name: &str,
+        type_name: &str,
+        new_name: &str,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let is_defining_module = self.find_module_name(content) == Some(module_name);
+        let is_in_scope = self.is_type_in_scope(module_name, type_name, content);
+
+        let mut edits = vec![];
+
+        if is_defining_module {
+            edits.extend(
+                self.find_all_nodes(NodeKind::is_type_name)
+                    .into_iter()
+                    .filter(|n| {
+                        n.parent()
+                            .is_some_and(|p| NodeKind::is_local_definition(&p))
+                    })
+                    .filter_map(|n| n.child(0))
+                    .filter(|n| n.utf8_text(content).is_ok_and(|text| text == type_name))
+                    .map(|n| Self::rename_edit(&n, new_name)),
+            );
+        }
+
+        for scoped_name in self.find_all_nodes(NodeKind::is_scoped_name) {
+            if scoped_name
+                .parent()
+                .is_some_and(|p| NodeKind::is_module_definition(&p))
+            {
+                continue;
+            }
+
+            if Self::refers_to_type(&scoped_name, module_name, type_name, is_in_scope, content) {
+                if let Some(last) = scoped_name.named_child(scoped_name.named_child_count() - 1) {
+                    edits.push(Self::rename_edit(&last, new_name));
+                }
+            }
+        }
+
+        edits
+    }
+
+    /// Find the edits that rename the field `field_name` of the type `module_name.type_name` in this document.
+    ///
+    /// This covers the field itself and `annotation Type::field` declarations. The keys of JSON values
+    /// depend on types resolved across the workspace, so they are renamed by the server.
+    pub fn field_rename_edits(
+        &self,
+        module_name: &str,
+        type_name: &str,
+        field_name: &str,
+        new_name: &str,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let is_defining_module = self.find_module_name(content) == Some(module_name);
+        let is_in_scope = self.is_type_in_scope(module_name, type_name, content);
+        let refers_to_type =
+            |n: &Node| Self::refers_to_type(n, module_name, type_name, is_in_scope, content);
+
+        let mut edits = vec![];
+
+        // the field definition
+        if is_defining_module {
+            edits.extend(
+                self.find_all_nodes(NodeKind::is_field)
+                    .into_iter()
+                    .filter(|n| {
+                        Self::find_enclosing_definition(n)
+                            .and_then(|d| Self::definition_name(&d, content))
+                            == Some(type_name)
+                    })
+                    .filter_map(|n| Self::field_name_node(&n))
+                    .filter(|n| n.utf8_text(content).is_ok_and(|text| text == field_name))
+                    .map(|n| Self::rename_edit(&n, new_name)),
+            );
+        }
+
+        // annotation declarations referencing the field
+        for declaration in self.find_all_nodes(NodeKind::is_annotation_declaration) {
+            let mut cursor = declaration.walk();
+            let mut children = declaration.named_children(&mut cursor);
+            let target_type = children.next();
+            let field_reference = children.next().filter(NodeKind::is_field_reference);
+
+            if let (Some(target_type), Some(field_reference)) = (target_type, field_reference) {
+                let field_identifier = field_reference.child(0);
+                if refers_to_type(&target_type)
+                    && field_identifier
+                        .and_then(|n| n.utf8_text(content).ok())
+                        .is_some_and(|text| text == field_name)
+                {
+                    edits.extend(field_identifier.map(|n| Self::rename_edit(&n, new_name)));
+                }
+            }
+        }
+
+        edits
+    }
+
+    /// Find the edits that rename the module `module_name` to `new_module_name` in this document.
+    ///
+    /// This covers the module declaration, explicit and star imports of the module and any fully
+    /// qualified references to its types.
+    pub fn module_rename_edits(
+        &self,
+        module_name: &str,
+        new_module_name: &str,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let mut edits = vec![];
+
+        for scoped_name in self.find_all_nodes(NodeKind::is_scoped_name) {
+            let identifiers: Vec<Node> = scoped_name
+                .children(&mut scoped_name.walk())
+                .filter(NodeKind::is_identifier)
+                .collect();
+
+            // the module declaration and star imports name the module alone,
+            // everything else is qualified by the module and ends with a type name
+            let names_module_alone = scoped_name.parent().is_some_and(|p| {
+                NodeKind::is_module_definition(&p)
+                    || (NodeKind::is_import_path(&p) && p.child_count() > 1)
+            });
+            let module_identifiers = if names_module_alone {
+                &identifiers[..]
+            } else {
+                identifiers.split_last().map_or(&[][..], |(_, rest)| rest)
+            };
+            let (Some(first), Some(last)) = (module_identifiers.first(), module_identifiers.last())
+            else {
+                continue;
+            };
+
+            let qualifier = module_identifiers
+                .iter()
+                .filter_map(|n| n.utf8_text(content).ok())
+                .collect::<Vec<_>>()
+                .join(".");
+            if qualifier == module_name {
+                edits.push(TextEdit {
+                    range: Range::new(
+                        ts_lsp_interop::ts_to_lsp_position(&first.start_position()),
+                        ts_lsp_interop::ts_to_lsp_position(&last.end_position()),
+                    ),
+                    new_text: new_module_name.to_string(),
+                });
+            }
+        }
+
+        edits
+    }
+
+    /// Check whether a type can be referenced by its unqualified name in this document
+    fn is_type_in_scope(&self, module_name: &str, type_name: &str, content: &[u8]) -> bool {
+        self.find_module_name(content) == Some(module_name)
+            || self.has_import(module_name, type_name, content)
+    }
+
+    /// Check whether a scoped name refers to the type `module_name.type_name`
+    fn refers_to_type(
+        scoped_name: &Node<'_>,
+        module_name: &str,
+        type_name: &str,
+        is_in_scope: bool,
+        content: &[u8],
+    ) -> bool {
+        if !NodeKind::is_scoped_name(scoped_name) {
+            return false;
+        }
+
+        let identifiers: Vec<Node> = scoped_name
+            .children(&mut scoped_name.walk())
+            .filter(NodeKind::is_identifier)
+            .collect();
+        let Some((last, qualifiers)) = identifiers.split_last() else {
+            return false;
+        };
+        if last.utf8_text(content).ok() != Some(type_name) {
+            return false;
+        }
+
+        if qualifiers.is_empty() {
+            is_in_scope && !Self::is_type_parameter_reference(scoped_name, type_name, content)
+        } else {
+            let qualifier = qualifiers
+                .iter()
+                .filter_map(|n| n.utf8_text(content).ok())
+                .collect::<Vec<_>>()
+                .join(".");
+            qualifier == module_name
+        }
+    }
+
+    /// Check whether an unqualified name refers to a type parameter of the enclosing definition
+    pub fn is_type_parameter_reference(node: &Node<'_>, name: &str, content: &[u8]) -> bool {
+        Self::find_enclosing_definition(node).is_some_and(|definition| {
+            Self::type_parameter_names(&definition, content).contains(&name)
+        })
+    }
+
+    /// The identifier naming a field
+    pub fn field_name_node<'a>(field: &Node<'a>) -> Option<Node<'a>> {
+        field
+            .children(&mut field.walk())
+            .find(NodeKind::is_identifier)
+    }
+
+    fn rename_edit(node: &Node<'_>, new_name: &str) -> TextEdit {
+        TextEdit {
+            range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+            new_text: new_name.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::Url;
+    use insta::assert_yaml_snapshot;
+
+    use crate::parser::AdlParser;
+
+    #[test]
+    fn test_type_rename_edits() {
+        let uri: Url = "file://input/rename.adl".parse().unwrap();
+        let contents = include_str!("input/rename.adl");
+
+        let mut parser = AdlParser::new();
+        let tree = parser.parse(uri, contents.as_bytes()).unwrap();
+
+        // defined in this module
+        let user_edits =
+            tree.type_rename_edits("input.rename", "User", "Person", contents.as_bytes());
+        assert_yaml_snapshot!(user_edits);
+
+        // explicitly imported and referenced with a fully qualified name
+        let string_edits = tree.type_rename_edits(
+            "common.strings",
+            "StringNE",
+            "NonEmpty",
+            contents.as_bytes(),
+        );
+        assert_yaml_snapshot!(string_edits);
+
+        // not in scope, so only the fully qualified reference is renamed
+        let id_edits = tree.type_rename_edits("common.db", "Id", "Key", contents.as_bytes());
+        assert_yaml_snapshot!(id_edits);
+    }
+
+    #[test]
+    fn test_field_rename_edits() {
+        let uri: Url = "file://input/rename.adl".parse().unwrap();
+        let contents = include_str!("input/rename.adl");
+
+        let mut parser = AdlParser::new();
+        let tree = parser.parse(uri, contents.as_bytes()).unwrap();
+
+        let name_edits = tree.field_rename_edits(
+            "input.rename",
+            "User",
+            "name",
+            "fullName",
+            contents.as_bytes(),
+        );
+        assert_yaml_snapshot!(name_edits);
+    }
+
+    #[test]
+    fn test_module_rename_edits() {
+        let uri: Url = "file://input/rename.adl".parse().unwrap();
+        let contents = include_str!("input/rename.adl");
+
+        let mut parser = AdlParser::new();
+        let tree = parser.parse(uri, contents.as_bytes()).unwrap();
+
+        // the module declaration of this document
+        let module_edits =
+            tree.module_rename_edits("input.rename", "input.renamed", contents.as_bytes());
+        assert_yaml_snapshot!(module_edits);
+
+        // an explicit import and a fully qualified reference
+        let import_edits =
+            tree.module_rename_edits("common.strings", "common.text", contents.as_bytes());
+        assert_yaml_snapshot!(import_edits);
+
+        // only a prefix of the module name matches
+        let prefix_edits = tree.module_rename_edits("common", "shared", contents.as_bytes());
+        assert!(prefix_edits.is_empty());
+    }
+}
@@ -0,0 +1,459 @@
+use std::collections::{HashMap, HashSet};
+
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use serde::Deserialize;
+use tracing::warn;
+use tree_sitter::Node;
+
+use crate::node::NodeKind;
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+
+/// A language that ADL generates code for, whose annotations live in `.adl-<extension>` files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Target {
+    Java,
+    Rust,
+    TypeScript,
+    Haskell,
+    Cpp,
+}
+
+impl Target {
+    /// The target whose annotation files have this suffix e.g. `java` for `.adl-java`
+    pub fn from_extension(extension: &str) -> Option<Target> {
+        match extension {
+            "java" => Some(Target::Java),
+            "rs" => Some(Target::Rust),
+            "ts" => Some(Target::TypeScript),
+            "hs" => Some(Target::Haskell),
+            "cpp" => Some(Target::Cpp),
+            _ => None,
+        }
+    }
+
+    /// Read the targets to check from the `targets` list of annotation file suffixes in the client
+    /// settings e.g. `["java", "ts"]`, ignoring any suffix that is not a target
+    pub fn from_settings(settings: &serde_json::Value) -> Result<Vec<Target>, serde_json::Error> {
+        let extensions = Vec::<String>::deserialize(settings)?;
+        Ok(extensions
+            .iter()
+            .filter_map(|extension| {
+                let target = Target::from_extension(extension);
+                if target.is_none() {
+                    warn!("ignoring configuration of unknown target: {}", extension);
+                }
+                target
+            })
+            .collect())
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Target::Java => "Java",
+            Target::Rust => "Rust",
+            Target::TypeScript => "TypeScript",
+            Target::Haskell => "Haskell",
+            Target::Cpp => "C++",
+        }
+    }
+
+    pub fn is_reserved(&self, word: &str) -> bool {
+        let reserved_words: &[&str] = match self {
+            Target::Java => JAVA_RESERVED_WORDS,
+            Target::Rust => RUST_RESERVED_WORDS,
+            Target::TypeScript => TYPESCRIPT_RESERVED_WORDS,
+            Target::Haskell => HASKELL_RESERVED_WORDS,
+            Target::Cpp => CPP_RESERVED_WORDS,
+        };
+        reserved_words.contains(&word)
+    }
+}
+
+const JAVA_RESERVED_WORDS: &[&str] = &[
+    "abstract",
+    "assert",
+    "boolean",
+    "break",
+    "byte",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extends",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "long",
+    "native",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "static",
+    "strictfp",
+    "super",
+    "switch",
+    "synchronized",
+    "this",
+    "throw",
+    "throws",
+    "transient",
+    "true",
+    "try",
+    "void",
+    "volatile",
+    "while",
+];
+
+const RUST_RESERVED_WORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "if", "impl", "in",
+    "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+const TYPESCRIPT_RESERVED_WORDS: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "let",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+];
+
+const HASKELL_RESERVED_WORDS: &[&str] = &[
+    "case", "class", "data", "default", "deriving", "do", "else", "foreign", "if", "import", "in",
+    "infix", "infixl", "infixr", "instance", "let", "module", "newtype", "of", "then", "type",
+    "where",
+];
+
+const CPP_RESERVED_WORDS: &[&str] = &[
+    "alignas",
+    "alignof",
+    "and",
+    "and_eq",
+    "asm",
+    "auto",
+    "bitand",
+    "bitor",
+    "bool",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "char8_t",
+    "char16_t",
+    "char32_t",
+    "class",
+    "compl",
+    "concept",
+    "const",
+    "consteval",
+    "constexpr",
+    "constinit",
+    "const_cast",
+    "continue",
+    "co_await",
+    "co_return",
+    "co_yield",
+    "decltype",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "dynamic_cast",
+    "else",
+    "enum",
+    "explicit",
+    "export",
+    "extern",
+    "false",
+    "float",
+    "for",
+    "friend",
+    "goto",
+    "if",
+    "inline",
+    "int",
+    "long",
+    "mutable",
+    "namespace",
+    "new",
+    "noexcept",
+    "not",
+    "not_eq",
+    "nullptr",
+    "operator",
+    "or",
+    "or_eq",
+    "private",
+    "protected",
+    "public",
+    "register",
+    "reinterpret_cast",
+    "requires",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "static_assert",
+    "static_cast",
+    "struct",
+    "switch",
+    "template",
+    "this",
+    "thread_local",
+    "throw",
+    "true",
+    "try",
+    "typedef",
+    "typeid",
+    "typename",
+    "union",
+    "unsigned",
+    "using",
+    "virtual",
+    "void",
+    "volatile",
+    "wchar_t",
+    "while",
+    "xor",
+    "xor_eq",
+];
+
+impl ParsedTree {
+    /// Report types, fields and variants named with a word that is reserved in one of the target
+    /// languages, unless the annotation file for that target renames them (see [`ParsedTree::find_renamed_names`])
+    pub fn collect_reserved_word_diagnostics(
+        &self,
+        content: &[u8],
+        targets: &[Target],
+        renamed_names: &HashMap<Target, HashSet<String>>,
+    ) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+
+        let breaking_targets = |name: &str, annotated_name: &str| -> Vec<Target> {
+            targets
+                .iter()
+                .copied()
+                .filter(|target| target.is_reserved(name))
+                .filter(|target| {
+                    renamed_names
+                        .get(target)
+                        .is_none_or(|renamed| !renamed.contains(annotated_name))
+                })
+                .collect()
+        };
+
+        for type_name in self.find_all_nodes(NodeKind::is_type_name) {
+            let Ok(name) = type_name.utf8_text(content) else {
+                continue;
+            };
+            let targets = breaking_targets(name, name);
+            if !targets.is_empty() {
+                diagnostics.push(reserved_word_diagnostic(&type_name, "type", name, &targets));
+            }
+        }
+
+        for field in self.find_all_nodes(NodeKind::is_field) {
+            let Some(name_node) = Self::field_name_node(&field) else {
+                continue;
+            };
+            let Ok(name) = name_node.utf8_text(content) else {
+                continue;
+            };
+            let Some(definition) = Self::find_enclosing_definition(&field) else {
+                continue;
+            };
+            let Some(type_name) = definition
+                .named_children(&mut definition.walk())
+                .find(NodeKind::is_type_name)
+                .and_then(|type_name| type_name.utf8_text(content).ok())
+            else {
+                continue;
+            };
+            let targets = breaking_targets(name, &format!("{}::{}", type_name, name));
+            if !targets.is_empty() {
+                let kind = if NodeKind::is_union_definition(&definition) {
+                    "variant"
+                } else {
+                    "field"
+                };
+                diagnostics.push(reserved_word_diagnostic(&name_node, kind, name, &targets));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// The types and fields that an annotation file renames in its target language, as `Type` or
+    /// `Type::field`. A declaration renames when its annotation is a name e.g. `JavaFieldName` in
+    /// `annotation Query::type JavaFieldName "type_";`, other than the `SerializedName` of the JSON
+    pub fn find_renamed_names(&self, content: &[u8]) -> HashSet<String> {
+        self.find_all_nodes(NodeKind::is_annotation_declaration)
+            .into_iter()
+            .filter_map(|declaration| {
+                let (annotation_type, _) = Self::annotation_type_and_value(&declaration)?;
+                let annotation_name = annotation_type.utf8_text(content).ok()?;
+                let annotation_name = annotation_name
+                    .rsplit_once('.')
+                    .map_or(annotation_name, |(_, name)| name);
+                if !annotation_name.ends_with("Name") || annotation_name == "SerializedName" {
+                    return None;
+                }
+
+                match self.annotation_declaration_target(&declaration, content)? {
+                    (type_name, Some(field_name)) => Some(format!("{}::{}", type_name, field_name)),
+                    (type_name, None) => Some(type_name.to_string()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn reserved_word_diagnostic(node: &Node, kind: &str, name: &str, targets: &[Target]) -> Diagnostic {
+    let target_names: Vec<&str> = targets.iter().map(Target::name).collect();
+    let target_names = match target_names.split_last() {
+        Some((last, [])) => last.to_string(),
+        Some((last, rest)) => format!("{} and {}", rest.join(", "), last),
+        None => String::new(),
+    };
+
+    Diagnostic {
+        range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+        severity: Some(DiagnosticSeverity::WARNING),
+        code: Some(NumberOrString::String(String::from("reserved-word"))),
+        message: format!(
+            "{} `{}` is a reserved word in {}, so the generated code will not compile",
+            kind, name, target_names
+        ),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use async_lsp::lsp_types::Url;
+    use insta::assert_yaml_snapshot;
+
+    use super::*;
+    use crate::parser::AdlParser;
+
+    #[test]
+    fn test_collect_reserved_word_diagnostics() {
+        let url: Url = "file://foo/reserved.adl".parse().unwrap();
+        let contents = include_str!("input/reserved.adl");
+
+        let parsed = AdlParser::new().parse(url.clone(), contents).unwrap();
+        let renamed_names = HashMap::from([
+            (
+                Target::Rust,
+                HashSet::from([String::from("Query::type"), String::from("Self")]),
+            ),
+            (
+                Target::Haskell,
+                HashSet::from([String::from("Query::data")]),
+            ),
+        ]);
+        let targets: Vec<Target> = ["java", "rs", "ts", "hs", "cpp"]
+            .into_iter()
+            .filter_map(Target::from_extension)
+            .collect();
+        assert_yaml_snapshot!(parsed.collect_reserved_word_diagnostics(
+            contents.as_bytes(),
+            &targets,
+            &renamed_names
+        ));
+    }
+
+    #[test]
+    fn test_target_from_settings() {
+        let targets = Target::from_settings(&serde_json::json!(["java", "rs", "py"])).unwrap();
+        assert_eq!(targets, vec![Target::Java, Target::Rust]);
+        assert!(Target::from_settings(&serde_json::json!({"java": true})).is_err());
+    }
+
+    #[test]
+    fn test_find_renamed_names() {
+        let url: Url = "file://foo/reserved.adl-rs".parse().unwrap();
+        let contents = r#"module input.reserved {
+  annotation Query::type RustFieldName "type_";
+  annotation input.reserved.Self adlc.config.rust.RustName "SelfType";
+  annotation Query::match SerializedName "match";
+  annotation Query::match Doc "not a rename";
+  annotation other.module.Query::data HaskellFieldName "data_";
+};
+"#;
+
+        let parsed = AdlParser::new().parse(url.clone(), contents).unwrap();
+        let mut renamed_names: Vec<String> = parsed
+            .find_renamed_names(contents.as_bytes())
+            .into_iter()
+            .collect();
+        renamed_names.sort();
+        assert_eq!(renamed_names, vec!["Query::type", "Self"]);
+    }
+}
@@ -0,0 +1,592 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use async_lsp::ResponseError;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse,
+    Diagnostic, DocumentChangeOperation, DocumentChanges, NumberOrString, OneOf,
+    OptionalVersionedTextDocumentIdentifier, Position, Range, RenameFile, RenameParams, ResourceOp,
+    TextDocumentEdit, TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, Url,
+    WorkDoneProgressParams, WorkspaceEdit,
+};
+
+use crate::node::NodeKind;
+use crate::parser::tree::Tree;
+use crate::parser::{ParsedTree, ts_lsp_interop};
+use crate::server::Server;
+use crate::server::imports::Fqn;
+use crate::server::packages;
+use crate::server::rename::is_valid_identifier;
+
+impl Server {
+    pub fn handle_code_action(
+        &mut self,
+        params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>, ResponseError> {
+        let uri = params.text_document.uri;
+
+        let Some((tree, content)) = self.get_or_parse_document_with_content(&uri) else {
+            return Ok(None);
+        };
+
+        let only = params.context.only.unwrap_or_default();
+        let is_requested = |kind: &CodeActionKind| {
+            only.is_empty()
+                || only.iter().any(|requested| {
+                    kind.as_str() == requested.as_str()
+                        || kind
+                            .as_str()
+                            .starts_with(&format!("{}.", requested.as_str()))
+                })
+        };
+
+        let mut actions = Vec::new();
+
+        if is_requested(&CodeActionKind::QUICKFIX) {
+            let mut positions = vec![params.range.start];
+            positions.extend(params.context.diagnostics.iter().map(|d| d.range.start));
+            positions.dedup();
+            for position in positions {
+                actions.extend(self.import_quick_fixes(
+                    &uri,
+                    &tree,
+                    &content,
+                    &position,
+                    &params.context.diagnostics,
+                ));
+            }
+            actions.extend(Self::remove_import_quick_fixes(
+                &uri,
+                &tree,
+                &content,
+                &params.context.diagnostics,
+            ));
+            actions.extend(self.module_path_quick_fixes(
+                &uri,
+                &tree,
+                &content,
+                &params.context.diagnostics,
+            ));
+            actions.extend(self.package_dependency_quick_fixes(&uri, &params.context.diagnostics));
+            actions.extend(self.naming_quick_fixes(
+                &uri,
+                &tree,
+                &content,
+                &params.context.diagnostics,
+            ));
+        }
+
+        let star_import_types = self.star_import_types(&uri);
+        if is_requested(&CodeActionKind::SOURCE_ORGANIZE_IMPORTS) {
+            let edits = tree.organize_imports_edits(&star_import_types, content.as_bytes());
+            if !edits.is_empty() {
+                actions.push(Self::code_action(
+                    "Organize imports",
+                    CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                    &uri,
+                    edits,
+                ));
+            }
+        }
+
+        if is_requested(&CodeActionKind::REFACTOR_REWRITE) {
+            let position = params.range.start;
+            if let Some((module_name, edit)) =
+                tree.expand_star_import_edit(&position, &star_import_types, content.as_bytes())
+            {
+                actions.push(Self::code_action(
+                    &format!("Replace `{}.*` with explicit imports", module_name),
+                    CodeActionKind::REFACTOR_REWRITE,
+                    &uri,
+                    vec![edit],
+                ));
+            }
+            if let Some((module_name, edits)) =
+                tree.collapse_imports_edits(&position, content.as_bytes())
+            {
+                actions.push(Self::code_action(
+                    &format!(
+                        "Replace imports from `{}` with `{}.*`",
+                        module_name, module_name
+                    ),
+                    CodeActionKind::REFACTOR_REWRITE,
+                    &uri,
+                    edits,
+                ));
+            }
+        }
+
+        if actions.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(actions))
+        }
+    }
+
+    /// Offer to import a type from another module when it is referenced without being in scope
+    fn import_quick_fixes(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &str,
+        position: &Position,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        let Some((identifier, node)) = tree.get_identifier_at(position, content.as_bytes()) else {
+            return vec![];
+        };
+
+        // only unqualified names in type expressions and annotations can be imported
+        let Some(scoped_name) = node.parent().filter(NodeKind::is_scoped_name) else {
+            return vec![];
+        };
+        if scoped_name.named_child_count() != 1
+            || !scoped_name.parent().is_some_and(|p| {
+                NodeKind::is_type_expression(&p)
+                    || NodeKind::is_annotation_decorator(&p)
+                    || NodeKind::is_annotation_declaration(&p)
+            })
+        {
+            return vec![];
+        }
+
+        let is_visible = self
+            .visible_type_items(uri, tree, content, position)
+            .iter()
+            .any(|item| item.label == identifier);
+        if is_visible {
+            return vec![];
+        }
+
+        let range = ts_lsp_interop::ts_to_lsp_range(&node.range());
+        let related_diagnostics: Vec<Diagnostic> = diagnostics
+            .iter()
+            .filter(|d| d.range == range)
+            .cloned()
+            .collect();
+
+        let module_name = tree
+            .find_module_name(content.as_bytes())
+            .unwrap_or_default();
+        let candidates: Vec<Fqn> = self
+            .state
+            .get_all_definitions()
+            .into_iter()
+            .map(|(fqn, _)| fqn)
+            .filter(|fqn| fqn.type_name() == identifier && fqn.module_name() != module_name)
+            .collect();
+        let is_preferred = candidates.len() == 1;
+
+        candidates
+            .into_iter()
+            .filter_map(|fqn| {
+                let edit = tree.import_insertion_edit(
+                    fqn.module_name(),
+                    fqn.type_name(),
+                    content.as_bytes(),
+                )?;
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Import `{}.{}`", fqn.module_name(), fqn.type_name()),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(related_diagnostics.clone()),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(is_preferred),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect()
+    }
+
+    /// Offer to delete imports that have been reported as unused or duplicated
+    fn remove_import_quick_fixes(
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &str,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        diagnostics
+            .iter()
+            .filter_map(|diagnostic| {
+                let title = match &diagnostic.code {
+                    Some(NumberOrString::String(code)) if code == "unused-import" => {
+                        "Remove unused import"
+                    }
+                    Some(NumberOrString::String(code)) if code == "duplicate-import" => {
+                        "Remove duplicate import"
+                    }
+                    _ => return None,
+                };
+                let edit = tree.import_removal_edit(&diagnostic.range.start, content.as_bytes())?;
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: title.to_string(),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect()
+    }
+
+    /// Offer to rename a misplaced module to match the location of its file, or to move the file to
+    /// where the module name says it should be so that its importers resolve it
+    fn module_path_quick_fixes(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &str,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        let Some(diagnostic) = diagnostics
+            .iter()
+            .find(|d| d.code == Some(NumberOrString::String(String::from("module-path-mismatch"))))
+        else {
+            return vec![];
+        };
+        let (Some(module_name), Some(package_root)) = (
+            tree.find_module_name(content.as_bytes()),
+            self.state.get_package_root(uri),
+        ) else {
+            return vec![];
+        };
+        let mut actions = vec![];
+
+        if let Some(expected_module_name) =
+            packages::module_name_for_path(&package_root, uri.path())
+                .filter(|name| name.split('.').all(is_valid_identifier))
+        {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Rename module to `{}`", expected_module_name),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    changes: Some(self.module_rename_changes(
+                        uri,
+                        module_name,
+                        &expected_module_name,
+                    )),
+                    ..WorkspaceEdit::default()
+                }),
+                is_preferred: Some(true),
+                ..CodeAction::default()
+            }));
+        }
+
+        let relative_path = format!("{}.adl", module_name.replace('.', "/"));
+        if let Some(operations) = self.file_move_operations(uri, &package_root.join(&relative_path))
+        {
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Move file to `{}`", relative_path),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit {
+                    document_changes: Some(DocumentChanges::Operations(operations)),
+                    ..WorkspaceEdit::default()
+                }),
+                ..CodeAction::default()
+            }));
+        }
+
+        actions
+    }
+
+    /// The operations that move a document and its companion files to a new path, unless a file
+    /// already exists there
+    fn file_move_operations(
+        &self,
+        uri: &Url,
+        new_path: &Path,
+    ) -> Option<Vec<DocumentChangeOperation>> {
+        if new_path.exists() {
+            return None;
+        }
+        let mut renames = vec![(uri.clone(), Url::from_file_path(new_path).ok()?)];
+        renames.extend(
+            self.companion_files(uri)
+                .into_iter()
+                .filter_map(|companion_uri| {
+                    let extension = Path::new(companion_uri.path()).extension()?;
+                    let new_companion_uri =
+                        Url::from_file_path(new_path.with_extension(extension)).ok()?;
+                    Some((companion_uri, new_companion_uri))
+                }),
+        );
+
+        Some(
+            renames
+                .into_iter()
+                .map(|(old_uri, new_uri)| {
+                    DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+                        old_uri,
+                        new_uri,
+                        options: None,
+                        annotation_id: None,
+                    }))
+                })
+                .collect(),
+        )
+    }
+
+    /// Offer to declare the package of an imported module as a dependency in the `adl-package.json`
+    /// of the importing package
+    fn package_dependency_quick_fixes(
+        &self,
+        uri: &Url,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        let Some(package_root) = self.state.get_package_root(uri) else {
+            return vec![];
+        };
+        let package_json_path = package_root.join("adl-package.json");
+        let (Ok(package_json_uri), Ok(package_json)) = (
+            Url::from_file_path(&package_json_path),
+            fs::read_to_string(&package_json_path),
+        ) else {
+            return vec![];
+        };
+
+        let mut localdirs = HashSet::new();
+        diagnostics
+            .iter()
+            .filter(|d| {
+                d.code
+                    == Some(NumberOrString::String(String::from(
+                        "undeclared-dependency",
+                    )))
+            })
+            .filter_map(|diagnostic| {
+                let localdir = diagnostic.data.as_ref()?.get("localdir")?.as_str()?;
+                if !localdirs.insert(localdir) {
+                    return None;
+                }
+                let (offset, new_text) = packages::dependency_insertion(&package_json, localdir)?;
+                let preceding = &package_json[..offset];
+                let line_start = preceding.rfind('\n').map_or(0, |i| i + 1);
+                // lsp positions count utf-16 code units rather than bytes
+                let position = Position::new(
+                    preceding.matches('\n').count() as u32,
+                    preceding[line_start..].encode_utf16().count() as u32,
+                );
+
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Add `{}` to the dependencies in adl-package.json", localdir),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: Some(vec![diagnostic.clone()]),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(
+                            package_json_uri.clone(),
+                            vec![TextEdit::new(Range::new(position, position), new_text)],
+                        )])),
+                        ..WorkspaceEdit::default()
+                    }),
+                    is_preferred: Some(true),
+                    ..CodeAction::default()
+                }))
+            })
+            .collect()
+    }
+
+    /// Offer to rename a name that breaks the naming conventions to its conventional form
+    fn naming_quick_fixes(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &str,
+        diagnostics: &[Diagnostic],
+    ) -> Vec<CodeActionOrCommand> {
+        let mut actions = vec![];
+
+        for diagnostic in diagnostics {
+            let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                continue;
+            };
+            let Some(new_name) = diagnostic
+                .data
+                .as_ref()
+                .and_then(|data| data.get("name"))
+                .and_then(|name| name.as_str())
+            else {
+                continue;
+            };
+
+            let edit = match code.as_str() {
+                "type-name-case" | "field-name-case" => {
+                    let Ok(Some(edit)) = self.handle_rename(RenameParams {
+                        text_document_position: TextDocumentPositionParams {
+                            text_document: TextDocumentIdentifier { uri: uri.clone() },
+                            position: diagnostic.range.start,
+                        },
+                        new_name: new_name.to_string(),
+                        work_done_progress_params: WorkDoneProgressParams::default(),
+                    }) else {
+                        continue;
+                    };
+                    edit
+                }
+                "type-parameter-case" => {
+                    let Some(parameter) = tree
+                        .find_all_nodes(NodeKind::is_identifier)
+                        .into_iter()
+                        .find(|n| ts_lsp_interop::ts_to_lsp_range(&n.range()) == diagnostic.range)
+                    else {
+                        continue;
+                    };
+                    let edits =
+                        tree.type_parameter_rename_edits(&parameter, new_name, content.as_bytes());
+                    WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), edits)])),
+                        ..WorkspaceEdit::default()
+                    }
+                }
+                "module-name-case" => {
+                    let Some(module_name) = tree.find_module_name(content.as_bytes()) else {
+                        continue;
+                    };
+                    self.module_case_rename_edit(uri, module_name, new_name)
+                }
+                _ => continue,
+            };
+
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: format!("Rename to `{}`", new_name),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(edit),
+                is_preferred: Some(true),
+                ..CodeAction::default()
+            }));
+        }
+
+        actions
+    }
+
+    /// Rename a module to its conventional name and move its files along with it, so that the
+    /// module is still found where its importers look for it
+    fn module_case_rename_edit(
+        &self,
+        uri: &Url,
+        module_name: &str,
+        new_module_name: &str,
+    ) -> WorkspaceEdit {
+        let changes = self.module_rename_changes(uri, module_name, new_module_name);
+        let Some(operations) = self.state.get_package_root(uri).and_then(|package_root| {
+            let relative_path = format!("{}.adl", new_module_name.replace('.', "/"));
+            self.file_move_operations(uri, &package_root.join(relative_path))
+        }) else {
+            return WorkspaceEdit {
+                changes: Some(changes),
+                ..WorkspaceEdit::default()
+            };
+        };
+
+        // the documents are edited under their old uris before they are moved
+        let mut changes: Vec<(Url, Vec<TextEdit>)> = changes.into_iter().collect();
+        changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut document_changes: Vec<DocumentChangeOperation> = changes
+            .into_iter()
+            .map(|(uri, edits)| {
+                DocumentChangeOperation::Edit(TextDocumentEdit {
+                    text_document: OptionalVersionedTextDocumentIdentifier { uri, version: None },
+                    edits: edits.into_iter().map(OneOf::Left).collect(),
+                })
+            })
+            .collect();
+        document_changes.extend(operations);
+
+        WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(document_changes)),
+            ..WorkspaceEdit::default()
+        }
+    }
+
+    /// The edits that rename the module declared by a document, along with its companion files.
+    /// Importers are only rewritten when they can't be relying on another copy of the module
+    fn module_rename_changes(
+        &self,
+        uri: &Url,
+        module_name: &str,
+        new_module_name: &str,
+    ) -> HashMap<Url, Vec<TextEdit>> {
+        let mut document_uris = self.companion_files(uri);
+        if self.state.get_documents_defining_module(module_name) == [uri.clone()] {
+            document_uris.extend(self.state.get_document_uris());
+        } else {
+            document_uris.push(uri.clone());
+        }
+        document_uris.sort();
+        document_uris.dedup();
+
+        let mut changes = HashMap::new();
+        for document_uri in document_uris {
+            let Some((document_tree, document_content)) =
+                self.state.get_document_tree_and_content(&document_uri)
+            else {
+                continue;
+            };
+            let edits = document_tree.module_rename_edits(
+                module_name,
+                new_module_name,
+                document_content.as_bytes(),
+            );
+            if !edits.is_empty() {
+                changes.insert(document_uri, edits);
+            }
+        }
+        changes
+    }
+
+    /// The annotation files (e.g. `.adl-java`) that accompany an ADL file
+    fn companion_files(&self, uri: &Url) -> Vec<Url> {
+        let path = Path::new(uri.path());
+        self.state
+            .get_document_uris()
+            .into_iter()
+            .filter(|document_uri| {
+                let document_path = Path::new(document_uri.path());
+                document_path.parent() == path.parent()
+                    && document_path.file_stem() == path.file_stem()
+                    && document_path
+                        .extension()
+                        .and_then(|ext| ext.to_str())
+                        .is_some_and(|ext| ext.starts_with("adl-"))
+            })
+            .collect()
+    }
+
+    /// The types defined by each module that the document imports, used to decide which of its star imports are used
+    fn star_import_types(&self, uri: &Url) -> HashMap<String, Vec<String>> {
+        let mut star_import_types = HashMap::<String, Vec<String>>::new();
+        for fqn in self.state.get_imported_types(uri) {
+            star_import_types
+                .entry(fqn.module_name().to_string())
+                .or_default()
+                .push(fqn.type_name().to_string());
+        }
+        star_import_types
+    }
+
+    fn code_action(
+        title: &str,
+        kind: CodeActionKind,
+        uri: &Url,
+        edits: Vec<TextEdit>,
+    ) -> CodeActionOrCommand {
+        CodeActionOrCommand::CodeAction(CodeAction {
+            title: title.to_string(),
+            kind: Some(kind),
+            edit: Some(WorkspaceEdit {
+                changes: Some(HashMap::from([(uri.clone(), edits)])),
+                ..WorkspaceEdit::default()
+            }),
+            ..CodeAction::default()
+        })
+    }
+}
@@ -1,12 +1,22 @@
 use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
 
 use crate::cli::{Cli, LspClient};
+use crate::parser::lints::LintConfig;
+use crate::parser::reserved::Target;
+use crate::server::ADL_EXTENSIONS;
 
 #[derive(Debug, Clone)]
 pub struct ServerConfig {
     _lsp_client: Option<LspClient>,
     /// Search dirs for adl packages specified by the user - does not include dependencies resolved from adl-package.json
     pub search_dirs: Vec<PathBuf>,
+    /// Lint rule levels chosen for the workspace in the client settings, shared with the clones of the
+    /// server that handle requests (e.g. `initialize`) so that their updates outlive the request
+    pub lints: Arc<RwLock<LintConfig>>,
+    /// Target languages whose reserved words are reported, every language with annotation files by default.
+    /// Shared like the lints
+    pub targets: Arc<RwLock<Vec<Target>>>,
 }
 
 impl From<&Cli> for ServerConfig {
@@ -22,6 +32,13 @@ impl ServerConfig {
             // Search dirs should already be resolved to paths (e.g. adl-vscode already resolved ${workspaceFolder} etc.)
             search_dirs: search_dirs.into_iter().map(PathBuf::from).collect(),
             _lsp_client: lsp_client,
+            lints: Arc::new(RwLock::new(LintConfig::default())),
+            targets: Arc::new(RwLock::new(
+                ADL_EXTENSIONS
+                    .into_iter()
+                    .filter_map(Target::from_extension)
+                    .collect(),
+            )),
         }
     }
 }
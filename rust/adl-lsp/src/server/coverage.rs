@@ -0,0 +1,154 @@
+use std::fmt;
+use std::path::PathBuf;
+
+use async_lsp::{ErrorCode, ResponseError};
+use lsp_types::{ExecuteCommandParams, Url};
+use serde::Serialize;
+use tracing::debug;
+
+use crate::server::Server;
+use crate::server::diagnostics::is_adl_file;
+
+/// The `workspace/executeCommand` command that reports the documentation coverage of the workspace
+pub const DOCUMENTATION_COVERAGE_COMMAND: &str = "adl.documentationCoverage";
+
+/// How many of the types, fields and variants in each package are documented
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentationCoverage {
+    pub documented: usize,
+    pub total: usize,
+    pub packages: Vec<PackageCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageCoverage {
+    pub package_root: PathBuf,
+    pub documented: usize,
+    pub total: usize,
+    pub modules: Vec<ModuleCoverage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModuleCoverage {
+    pub module_name: String,
+    pub uri: Url,
+    pub documented: usize,
+    pub total: usize,
+    /// The types, and `Type::field` fields and variants, that have no documentation
+    pub undocumented: Vec<String>,
+}
+
+impl DocumentationCoverage {
+    /// The percentage of the workspace that is documented, which is complete when there is nothing to document
+    pub fn percentage(&self) -> f64 {
+        percentage(self.documented, self.total)
+    }
+}
+
+fn percentage(documented: usize, total: usize) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        documented as f64 * 100.0 / total as f64
+    }
+}
+
+impl fmt::Display for DocumentationCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for package in &self.packages {
+            writeln!(
+                f,
+                "{}: {}/{} documented ({:.1}%)",
+                package.package_root.display(),
+                package.documented,
+                package.total,
+                percentage(package.documented, package.total)
+            )?;
+            for module in &package.modules {
+                writeln!(
+                    f,
+                    "  {}: {}/{} documented ({:.1}%)",
+                    module.module_name,
+                    module.documented,
+                    module.total,
+                    percentage(module.documented, module.total)
+                )?;
+                if !module.undocumented.is_empty() {
+                    writeln!(f, "    undocumented: {}", module.undocumented.join(", "))?;
+                }
+            }
+        }
+        writeln!(
+            f,
+            "total: {}/{} documented ({:.1}%)",
+            self.documented,
+            self.total,
+            self.percentage()
+        )
+    }
+}
+
+impl Server {
+    pub fn handle_execute_command(
+        &mut self,
+        params: ExecuteCommandParams,
+    ) -> Result<Option<serde_json::Value>, ResponseError> {
+        match params.command.as_str() {
+            DOCUMENTATION_COVERAGE_COMMAND => serde_json::to_value(self.documentation_coverage())
+                .map(Some)
+                .map_err(|e| ResponseError::new(ErrorCode::INTERNAL_ERROR, e.to_string())),
+            command => Err(ResponseError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("unknown command: {}", command),
+            )),
+        }
+    }
+
+    /// Report the documentation coverage of each module, grouped by package root
+    pub fn documentation_coverage(&self) -> DocumentationCoverage {
+        let mut coverage = DocumentationCoverage::default();
+
+        for (package_root, adl_files) in self.state.get_package_adl_files() {
+            debug!(
+                "collecting documentation coverage for package root: {}",
+                package_root.display()
+            );
+            let modules: Vec<ModuleCoverage> = adl_files
+                .into_iter()
+                .filter(is_adl_file)
+                .filter_map(|uri| {
+                    let (tree, content) = self.state.get_document_tree_and_content(&uri)?;
+                    let module_name = tree.find_module_name(content.as_bytes())?.to_string();
+                    let items = tree.find_documentable_items(content.as_bytes());
+                    let undocumented: Vec<String> = items
+                        .iter()
+                        .filter(|item| !item.documented)
+                        .map(|item| item.name.clone())
+                        .collect();
+                    Some(ModuleCoverage {
+                        module_name,
+                        uri,
+                        documented: items.len() - undocumented.len(),
+                        total: items.len(),
+                        undocumented,
+                    })
+                })
+                .collect();
+
+            let package = PackageCoverage {
+                package_root,
+                documented: modules.iter().map(|module| module.documented).sum(),
+                total: modules.iter().map(|module| module.total).sum(),
+                modules,
+            };
+            coverage.documented += package.documented;
+            coverage.total += package.total;
+            coverage.packages.push(package);
+        }
+
+        coverage
+    }
+}
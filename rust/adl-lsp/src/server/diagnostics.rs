@@ -0,0 +1,919 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+use async_lsp::lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, NumberOrString, Url,
+};
+use tree_sitter::Node;
+
+use crate::node::{AdlImportDeclaration, AdlPrimitive, NodeKind};
+use crate::parser::reserved::Target;
+use crate::parser::tree::Tree;
+use crate::parser::{ParsedTree, ts_lsp_interop};
+use crate::server::imports::Fqn;
+use crate::server::state::AdlLanguageServerState;
+use crate::server::typecheck::JsonTypeChecker;
+use crate::server::{ADL_EXTENSIONS, packages};
+
+/// Semantic checks that need to look beyond a single document
+impl AdlLanguageServerState {
+    /// Report every type expression naming a type that is not a primitive, a type parameter in scope,
+    /// a local definition, an imported type or a fully qualified type defined by another module
+    pub(super) fn collect_type_reference_diagnostics(
+        &self,
+        search_dirs: &HashMap<PathBuf, HashSet<Url>>,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        let Some(module_name) = tree.find_module_name(content) else {
+            return vec![];
+        };
+        let local_type_names = tree.find_local_type_names(content);
+        let imported_types = self.get_imported_types(uri);
+
+        let imports: Vec<AdlImportDeclaration> = tree
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .collect();
+        // names that are explicitly imported are reported on the import when they don't resolve
+        let explicitly_imported_names: Vec<&str> = imports
+            .iter()
+            .filter_map(|import| import.imported_type_name(content))
+            .collect();
+
+        let mut diagnostics = vec![];
+
+        for scoped_name in tree.find_all_nodes(NodeKind::is_scoped_name) {
+            if !scoped_name
+                .parent()
+                .is_some_and(|p| NodeKind::is_type_expression(&p))
+            {
+                continue;
+            }
+
+            let identifiers: Vec<&str> = scoped_name
+                .children(&mut scoped_name.walk())
+                .filter(NodeKind::is_identifier)
+                .filter_map(|n| n.utf8_text(content).ok())
+                .collect();
+            let Some((type_name, qualifiers)) = identifiers.split_last() else {
+                continue;
+            };
+
+            let message = if qualifiers.is_empty() {
+                // a star import of a module that cannot be found is reported on the import, and is
+                // not taken to bring every unknown name into scope
+                let is_known = AdlPrimitive::from_name(type_name).is_some()
+                    || ParsedTree::is_type_parameter_reference(&scoped_name, type_name, content)
+                    || local_type_names.contains(type_name)
+                    || explicitly_imported_names.contains(type_name)
+                    || imported_types
+                        .iter()
+                        .any(|fqn| fqn.type_name() == *type_name);
+                if is_known {
+                    continue;
+                }
+                format!("cannot find type `{}` in this scope", type_name)
+            } else {
+                let qualifier = qualifiers.join(".");
+                if qualifier == module_name {
+                    if local_type_names.contains(type_name) {
+                        continue;
+                    }
+                    format!(
+                        "module `{}` has no definition named `{}`",
+                        qualifier, type_name
+                    )
+                } else {
+                    let fqn = Fqn::from_module_name_and_type_name(&qualifier, type_name);
+                    match Self::resolve_qualified_type(
+                        search_dirs,
+                        uri,
+                        module_name,
+                        &fqn,
+                        get_or_parse_document,
+                    ) {
+                        Ok(()) => continue,
+                        Err(message) => message,
+                    }
+                }
+            };
+
+            diagnostics.push(Self::unknown_type_diagnostic(&scoped_name, message));
+        }
+
+        diagnostics
+    }
+
+    /// Report every instantiation of a user defined type with a different number of type arguments
+    /// than the definition has type parameters, whether the type is local or defined by another module
+    pub(super) fn collect_type_arity_diagnostics(
+        &self,
+        search_dirs: &HashMap<PathBuf, HashSet<Url>>,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        let Some(module_name) = tree.find_module_name(content) else {
+            return vec![];
+        };
+        let imported_types = self.get_imported_types(uri);
+        let imports: Vec<AdlImportDeclaration> = tree
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .collect();
+
+        let mut diagnostics = vec![];
+
+        for type_expression in tree.find_all_nodes(NodeKind::is_type_expression) {
+            let mut cursor = type_expression.walk();
+            let Some(scoped_name) = type_expression
+                .children(&mut cursor)
+                .find(NodeKind::is_scoped_name)
+            else {
+                continue;
+            };
+            let given = type_expression
+                .children(&mut cursor)
+                .find(NodeKind::is_type_arguments)
+                .map_or(0, |type_arguments| type_arguments.named_child_count());
+
+            let identifiers: Vec<&str> = scoped_name
+                .children(&mut scoped_name.walk())
+                .filter(NodeKind::is_identifier)
+                .filter_map(|n| n.utf8_text(content).ok())
+                .collect();
+            let Some((type_name, qualifiers)) = identifiers.split_last() else {
+                continue;
+            };
+
+            let fqn = if !qualifiers.is_empty() {
+                Fqn::from_module_name_and_type_name(&qualifiers.join("."), type_name)
+            } else if ParsedTree::is_type_parameter_reference(&scoped_name, type_name, content) {
+                if given > 0 {
+                    diagnostics.push(ParsedTree::type_arity_diagnostic(
+                        ts_lsp_interop::ts_to_lsp_range(&type_expression.range()),
+                        type_name,
+                        0,
+                        given,
+                    ));
+                }
+                continue;
+            } else if tree.find_local_type_names(content).contains(type_name) {
+                Fqn::from_module_name_and_type_name(module_name, type_name)
+            } else if let Some(import) = imports
+                .iter()
+                .find(|import| import.imported_type_name(content) == Some(type_name))
+            {
+                Fqn::from_module_name_and_type_name(import.module_name(content), type_name)
+            } else {
+                // ambiguous and unknown names are reported elsewhere
+                let mut candidates = imported_types
+                    .iter()
+                    .filter(|fqn| fqn.type_name() == *type_name);
+                match (candidates.next(), candidates.next()) {
+                    (Some(fqn), None) => fqn.clone(),
+                    _ => continue,
+                }
+            };
+
+            let expected = if fqn.module_name() == module_name {
+                Self::type_parameter_count(tree, content, fqn.type_name())
+            } else {
+                Self::resolve_module_document(
+                    search_dirs,
+                    uri,
+                    module_name,
+                    &fqn,
+                    get_or_parse_document,
+                )
+                .and_then(|(target_tree, target_content)| {
+                    Self::type_parameter_count(
+                        &target_tree,
+                        target_content.as_bytes(),
+                        fqn.type_name(),
+                    )
+                })
+            };
+            let Some(expected) = expected else {
+                continue;
+            };
+
+            if expected != given {
+                diagnostics.push(ParsedTree::type_arity_diagnostic(
+                    ts_lsp_interop::ts_to_lsp_range(&type_expression.range()),
+                    type_name,
+                    expected,
+                    given,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// The number of type parameters of a type defined by a document
+    fn type_parameter_count(tree: &ParsedTree, content: &[u8], type_name: &str) -> Option<usize> {
+        tree.find_all_nodes(NodeKind::is_local_definition)
+            .into_iter()
+            .find(|definition| ParsedTree::definition_name(definition, content) == Some(type_name))
+            .map(|definition| ParsedTree::type_parameter_names(&definition, content).len())
+    }
+
+    /// Report default values of fields and newtypes, and annotation values, that do not conform to
+    /// their declared type
+    pub(super) fn collect_json_value_diagnostics(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        let mut checker = JsonTypeChecker::new(self.imports(), get_or_parse_document);
+        let mut diagnostics = vec![];
+
+        for n in
+            tree.find_all_nodes(|n| NodeKind::is_field(n) || NodeKind::is_newtype_definition(n))
+        {
+            let Some((type_expression, json_value)) = ParsedTree::type_expression_and_default(&n)
+            else {
+                continue;
+            };
+
+            let json_type = checker.resolve_type_expression(uri, tree, content, &type_expression);
+            diagnostics.extend(
+                checker
+                    .check(&json_type, &json_value, content)
+                    .into_iter()
+                    .map(|error| Diagnostic {
+                        range: error.range,
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: Some(NumberOrString::String(String::from("invalid-default"))),
+                        message: error.message,
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        // annotation values must conform to the type of the annotation
+        for annotation in tree.find_all_nodes(|n| {
+            NodeKind::is_annotation_decorator(n) || NodeKind::is_annotation_declaration(n)
+        }) {
+            let Some((annotation_type, json_value)) =
+                ParsedTree::annotation_type_and_value(&annotation)
+            else {
+                continue;
+            };
+
+            let json_type = checker.resolve_annotation_type(uri, tree, content, &annotation_type);
+            diagnostics.extend(
+                checker
+                    .check(&json_type, &json_value, content)
+                    .into_iter()
+                    .map(|error| Diagnostic {
+                        range: error.range,
+                        severity: Some(DiagnosticSeverity::ERROR),
+                        code: Some(NumberOrString::String(String::from(
+                            "invalid-annotation-value",
+                        ))),
+                        message: error.message,
+                        ..Default::default()
+                    }),
+            );
+        }
+
+        diagnostics
+    }
+
+    /// Report `type` aliases that are ultimately defined as themselves, possibly through aliases in
+    /// other modules, which the ADL compiler cannot expand
+    pub(super) fn collect_alias_cycle_diagnostics(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        let Some(module_name) = tree.find_module_name(content) else {
+            return vec![];
+        };
+        let mut checker = JsonTypeChecker::new(self.imports(), get_or_parse_document);
+
+        tree.find_all_nodes(NodeKind::is_type_definition)
+            .into_iter()
+            .filter_map(|definition| {
+                let type_name_node = definition
+                    .children(&mut definition.walk())
+                    .find(NodeKind::is_type_name)?;
+                let type_name = type_name_node.utf8_text(content).ok()?;
+                let cycle = checker.find_alias_cycle(uri, tree, content, type_name)?;
+
+                let path: Vec<String> = cycle
+                    .iter()
+                    .chain(cycle.first())
+                    .map(|fqn| {
+                        if fqn.module_name() == module_name {
+                            format!("`{}`", fqn.type_name())
+                        } else {
+                            format!("`{}.{}`", fqn.module_name(), fqn.type_name())
+                        }
+                    })
+                    .collect();
+                Some(Diagnostic {
+                    range: ts_lsp_interop::ts_to_lsp_range(&type_name_node.range()),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(String::from("cyclic-type-alias"))),
+                    message: format!(
+                        "type alias `{}` is defined in terms of itself: {}",
+                        type_name,
+                        path.join(" -> ")
+                    ),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Report structs that must always contain a value of themselves, and unions none of whose
+    /// variants can be constructed, since no finite value of either exists
+    pub(super) fn collect_unconstructible_type_diagnostics(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        let mut checker = JsonTypeChecker::new(self.imports(), get_or_parse_document);
+
+        tree.find_all_nodes(|n| {
+            NodeKind::is_struct_definition(n) || NodeKind::is_union_definition(n)
+        })
+        .into_iter()
+        .filter_map(|definition| {
+            let type_name_node = definition
+                .children(&mut definition.walk())
+                .find(NodeKind::is_type_name)?;
+            let type_name = type_name_node.utf8_text(content).ok()?;
+            let is_union = NodeKind::is_union_definition(&definition);
+            if checker.is_constructible(uri, tree, content, type_name) {
+                return None;
+            }
+
+            let chain = checker
+                .find_self_containment(uri, tree, content, type_name)
+                .map(|chain| {
+                    chain
+                        .iter()
+                        .map(String::as_str)
+                        .chain([type_name])
+                        .map(|step| format!("`{}`", step))
+                        .collect::<Vec<_>>()
+                        .join(" -> ")
+                });
+            let message = match (is_union, chain) {
+                (false, Some(chain)) => format!(
+                    "struct `{}` can never be constructed because it always contains itself: {}",
+                    type_name, chain
+                ),
+                // structs that only contain another unconstructible type are reported there
+                (false, None) => return None,
+                (true, Some(chain)) => format!(
+                    "union `{}` can never be constructed because none of its variants can be: {}",
+                    type_name, chain
+                ),
+                (true, None) => format!(
+                    "union `{}` can never be constructed because none of its variants can be",
+                    type_name
+                ),
+            };
+            Some(Diagnostic {
+                range: ts_lsp_interop::ts_to_lsp_range(&type_name_node.range()),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(String::from("unconstructible-type"))),
+                message,
+                ..Default::default()
+            })
+        })
+        .collect()
+    }
+
+    /// Report star imports that bring types into scope without any of them being used
+    pub(super) fn collect_unused_star_import_diagnostics(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+    ) -> Vec<Diagnostic> {
+        let imported_types = self.get_imported_types(uri);
+        let references = tree.find_unqualified_references(content);
+
+        let imports: Vec<AdlImportDeclaration> = tree
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .collect();
+        let explicitly_imported_names: Vec<&str> = imports
+            .iter()
+            .filter_map(|import| import.imported_type_name(content))
+            .collect();
+
+        let mut seen_star_imports = HashSet::new();
+        imports
+            .iter()
+            .filter(|import| import.imported_type_name(content).is_none())
+            // repeated star imports are reported as duplicates
+            .filter(|import| seen_star_imports.insert(import.module_name(content)))
+            .filter_map(|import| {
+                let module_name = import.module_name(content);
+                let provided_names: Vec<&str> = imported_types
+                    .iter()
+                    .filter(|fqn| fqn.module_name() == module_name)
+                    .map(|fqn| fqn.type_name())
+                    .collect();
+
+                // an unresolved star import is reported by the ImportsCache, and one that clashes
+                // with a local definition is reported as ambiguous
+                let is_used = provided_names.is_empty()
+                    || provided_names.iter().any(|name| {
+                        references.contains(name) && !explicitly_imported_names.contains(name)
+                    });
+                (!is_used).then(|| {
+                    ParsedTree::unnecessary_import_diagnostic(
+                        import.inner(),
+                        "unused-import",
+                        format!("no types from `{}.*` are used", module_name),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Find the unqualified names that are brought into scope by more than one star import, or by a
+    /// star import and a local definition, along with every type they could refer to
+    ///
+    /// Explicit imports take precedence over star imports so they never make a name ambiguous.
+    pub fn find_ambiguous_names(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+    ) -> BTreeMap<String, Vec<Fqn>> {
+        let mut type_names: BTreeSet<String> = self
+            .get_imported_types(uri)
+            .iter()
+            .map(|fqn| fqn.type_name().to_string())
+            .collect();
+        type_names.extend(
+            tree.find_local_type_names(content)
+                .into_iter()
+                .map(String::from),
+        );
+
+        type_names
+            .into_iter()
+            .filter_map(|type_name| {
+                let fqns = self
+                    .imports()
+                    .lookup_unqualified_name(uri, tree, content, &type_name);
+                (fqns.len() > 1).then_some((type_name, fqns))
+            })
+            .collect()
+    }
+
+    /// Report every unqualified reference to an ambiguous name, along with the star imports that
+    /// make it ambiguous
+    pub(super) fn collect_ambiguous_import_diagnostics(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+    ) -> Vec<Diagnostic> {
+        let ambiguous_names = self.find_ambiguous_names(uri, tree, content);
+        if ambiguous_names.is_empty() {
+            return vec![];
+        }
+        let Some(module_name) = tree.find_module_name(content) else {
+            return vec![];
+        };
+
+        let star_imports: Vec<AdlImportDeclaration> = tree
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .filter(|import| import.imported_type_name(content).is_none())
+            .collect();
+        let location = |node: &Node| Location {
+            uri: uri.clone(),
+            range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+        };
+
+        let mut diagnostics = vec![];
+
+        for (type_name, fqns) in &ambiguous_names {
+            // the star imports and local definition that each bring the name into scope
+            let mut sources: Vec<(Location, String)> = vec![];
+            for fqn in fqns {
+                if fqn.module_name() == module_name {
+                    let definitions = tree
+                        .find_all_nodes(NodeKind::is_type_name)
+                        .into_iter()
+                        .filter(|n| {
+                            n.parent()
+                                .is_some_and(|p| NodeKind::is_local_definition(&p))
+                                && n.utf8_text(content).ok() == Some(type_name.as_str())
+                        });
+                    sources.extend(
+                        definitions
+                            .map(|n| (location(&n), format!("`{}` is defined here", type_name))),
+                    );
+                } else {
+                    sources.extend(
+                        star_imports
+                            .iter()
+                            .filter(|import| import.module_name(content) == fqn.module_name())
+                            .map(|import| {
+                                (
+                                    location(import.inner()),
+                                    format!(
+                                        "`{}` is imported from `{}` here",
+                                        type_name,
+                                        fqn.module_name()
+                                    ),
+                                )
+                            }),
+                    );
+                }
+            }
+            let related_information = |excluded: Option<&Location>| {
+                sources
+                    .iter()
+                    .filter(|(source, _)| Some(source) != excluded)
+                    .map(|(source, message)| DiagnosticRelatedInformation {
+                        location: source.clone(),
+                        message: message.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let usages: Vec<Node> = tree
+                .find_all_nodes(NodeKind::is_scoped_name)
+                .into_iter()
+                .filter(|n| {
+                    n.parent().is_some_and(|p| {
+                        !NodeKind::is_module_definition(&p) && !NodeKind::is_import_path(&p)
+                    }) && n.named_child_count() == 1
+                        && n.utf8_text(content).ok() == Some(type_name.as_str())
+                        && !ParsedTree::is_type_parameter_reference(n, type_name, content)
+                })
+                .collect();
+            // an ambiguous name is only an error once it is used
+            if usages.is_empty() {
+                continue;
+            }
+
+            let candidates = fqns
+                .iter()
+                .map(|fqn| format!("`{}.{}`", fqn.module_name(), fqn.type_name()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            for usage in &usages {
+                diagnostics.push(Diagnostic {
+                    range: ts_lsp_interop::ts_to_lsp_range(&usage.range()),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(String::from("ambiguous-type"))),
+                    message: format!(
+                        "`{}` is ambiguous, it could be any of {}",
+                        type_name, candidates
+                    ),
+                    related_information: Some(related_information(None)),
+                    ..Default::default()
+                });
+            }
+
+            for import in &star_imports {
+                let import_module = import.module_name(content);
+                if !fqns.iter().any(|fqn| fqn.module_name() == import_module) {
+                    continue;
+                }
+                let import_location = location(import.inner());
+                diagnostics.push(Diagnostic {
+                    range: import_location.range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(String::from("ambiguous-import"))),
+                    message: format!(
+                        "`{}.*` imports `{}`, which is also in scope from {}",
+                        import_module,
+                        type_name,
+                        fqns.iter()
+                            .filter(|fqn| fqn.module_name() != import_module)
+                            .map(|fqn| format!("`{}`", fqn.module_name()))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                    related_information: Some(related_information(Some(&import_location))),
+                    ..Default::default()
+                });
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Report a module that is also defined by another file, such as a copy of the module vendored
+    /// into a dependency, since every type it defines would then have two definitions
+    ///
+    /// Takes the documents and trees directly as it runs while they are being updated.
+    pub(super) fn collect_duplicate_module_diagnostics(
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        documents: &HashMap<Url, String>,
+        trees: &HashMap<Url, ParsedTree>,
+    ) -> Vec<Diagnostic> {
+        // companion annotation files (e.g. `.adl-java`) declare the module that they annotate
+        if !is_adl_file(uri) {
+            return vec![];
+        }
+        let (Some(module_name), Some(module_name_node)) =
+            (tree.find_module_name(content), Self::module_name_node(tree))
+        else {
+            return vec![];
+        };
+
+        let mut related_information: Vec<DiagnosticRelatedInformation> = trees
+            .iter()
+            .filter(|(other_uri, _)| *other_uri != uri && is_adl_file(other_uri))
+            .filter_map(|(other_uri, other_tree)| {
+                let other_content = documents.get(other_uri)?.as_bytes();
+                if other_tree.find_module_name(other_content) != Some(module_name) {
+                    return None;
+                }
+                let other_module_name_node = Self::module_name_node(other_tree)?;
+                Some(DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: other_uri.clone(),
+                        range: ts_lsp_interop::ts_to_lsp_range(&other_module_name_node.range()),
+                    },
+                    message: format!("`{}` is also defined here", module_name),
+                })
+            })
+            .collect();
+        if related_information.is_empty() {
+            return vec![];
+        }
+        related_information.sort_by(|a, b| a.location.uri.cmp(&b.location.uri));
+
+        let other_paths = related_information
+            .iter()
+            .map(|related| related.location.uri.path())
+            .collect::<Vec<_>>()
+            .join(", ");
+        vec![Diagnostic {
+            range: ts_lsp_interop::ts_to_lsp_range(&module_name_node.range()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(String::from("duplicate-definition"))),
+            message: format!(
+                "module `{}` is also defined by {}",
+                module_name, other_paths
+            ),
+            related_information: Some(related_information),
+            ..Default::default()
+        }]
+    }
+
+    /// Report a module whose name does not match the location of its file under the package root,
+    /// as imports of the module are resolved from its name
+    pub(super) fn collect_module_path_diagnostics(
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        package_root: Option<&PathBuf>,
+    ) -> Vec<Diagnostic> {
+        if !is_adl_file(uri) {
+            return vec![];
+        }
+        let (Some(module_name), Some(module_name_node), Some(expected_module_name)) = (
+            tree.find_module_name(content),
+            Self::module_name_node(tree),
+            package_root.and_then(|root| packages::module_name_for_path(root, uri.path())),
+        ) else {
+            return vec![];
+        };
+        if module_name == expected_module_name {
+            return vec![];
+        }
+
+        vec![Diagnostic {
+            range: ts_lsp_interop::ts_to_lsp_range(&module_name_node.range()),
+            severity: Some(DiagnosticSeverity::WARNING),
+            code: Some(NumberOrString::String(String::from("module-path-mismatch"))),
+            message: format!(
+                "module `{}` cannot be imported from this location, which must declare `module {}`",
+                module_name, expected_module_name
+            ),
+            ..Default::default()
+        }]
+    }
+
+    /// Report imports of modules from another package that is not declared as a dependency in the
+    /// `adl-package.json` of the importing package, which the ADL compiler would fail to find
+    pub(super) fn collect_package_dependency_diagnostics(
+        &self,
+        search_dirs: &HashMap<PathBuf, HashSet<Url>>,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        package_root: Option<&PathBuf>,
+    ) -> Vec<Diagnostic> {
+        let (Some(module_name), Some(package_root)) =
+            (tree.find_module_name(content), package_root)
+        else {
+            return vec![];
+        };
+        let Some(package_definition) = self.get_package_definition(package_root) else {
+            return vec![];
+        };
+        let package_root = packages::normalize_path(package_root);
+        let dependency_roots: Vec<PathBuf> = package_definition
+            .dependencies
+            .iter()
+            .map(|dependency| {
+                packages::normalize_path(packages::resolve_dependency_path(
+                    &package_root,
+                    &dependency.localdir,
+                ))
+            })
+            .collect();
+
+        tree.find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(|node| {
+                let imported_module = AdlImportDeclaration::try_new(node)?.module_name(content);
+                if imported_module.split('.').next() == Some(packages::STANDARD_LIBRARY_MODULE) {
+                    return None;
+                }
+                let target_uri = packages::resolve_import(
+                    search_dirs,
+                    uri,
+                    module_name,
+                    &imported_module.split('.').collect(),
+                    &|path| fs::exists(path).is_ok_and(|exists| exists),
+                )?;
+                let target_root = packages::normalize_path(packages::find_package_root_by_marker(
+                    target_uri.path(),
+                )?);
+                if target_root == package_root || dependency_roots.contains(&target_root) {
+                    return None;
+                }
+
+                let target_package_name = self
+                    .get_package_definition(&target_root)
+                    .map_or_else(|| target_root.display().to_string(), |d| d.name);
+                let localdir = packages::relative_path(&package_root, &target_root);
+                Some(Diagnostic {
+                    range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    code: Some(NumberOrString::String(String::from("undeclared-dependency"))),
+                    message: format!(
+                        "module `{}` belongs to package `{}`, which is not a dependency of package `{}`",
+                        imported_module, target_package_name, package_definition.name
+                    ),
+                    // the quick fix adds this directory to the dependencies of the package
+                    data: Some(serde_json::json!({ "localdir": localdir })),
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Report names that are reserved words in one of the configured target languages, unless the
+    /// annotation file for that target (e.g. `.adl-rs` for Rust) renames them
+    pub(super) fn collect_reserved_word_diagnostics(
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        targets: &[Target],
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
+        if !is_adl_file(uri) {
+            return vec![];
+        }
+
+        let mut renamed_names = HashMap::new();
+        for extension in ADL_EXTENSIONS {
+            let Some(target) = Target::from_extension(extension) else {
+                continue;
+            };
+            if !targets.contains(&target) {
+                continue;
+            }
+            let annotation_path =
+                Path::new(uri.path()).with_extension(format!("adl-{}", extension));
+            let Some((annotation_tree, annotation_content)) = Url::from_file_path(annotation_path)
+                .ok()
+                .and_then(|annotation_uri| get_or_parse_document(&annotation_uri))
+            else {
+                continue;
+            };
+            renamed_names.insert(
+                target,
+                annotation_tree.find_renamed_names(annotation_content.as_bytes()),
+            );
+        }
+
+        tree.collect_reserved_word_diagnostics(content, targets, &renamed_names)
+    }
+
+    /// The scoped name of the module declaration
+    fn module_name_node(tree: &ParsedTree) -> Option<Node<'_>> {
+        let module_definition = tree.find_first_node(NodeKind::is_module_definition)?;
+        module_definition
+            .named_children(&mut module_definition.walk())
+            .find(NodeKind::is_scoped_name)
+    }
+
+    /// Check that a fully qualified type is defined by a module that can be found from the document
+    fn resolve_qualified_type(
+        search_dirs: &HashMap<PathBuf, HashSet<Url>>,
+        uri: &Url,
+        module_name: &str,
+        fqn: &Fqn,
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Result<(), String> {
+        let target = Self::resolve_module_document(
+            search_dirs,
+            uri,
+            module_name,
+            fqn,
+            get_or_parse_document,
+        );
+
+        let Some((target_tree, target_content)) = target else {
+            return Err(format!("cannot find module `{}`", fqn.module_name()));
+        };
+
+        if target_tree
+            .find_local_type_names(target_content.as_bytes())
+            .contains(&fqn.type_name())
+        {
+            Ok(())
+        } else {
+            Err(format!(
+                "module `{}` has no definition named `{}`",
+                fqn.module_name(),
+                fqn.type_name()
+            ))
+        }
+    }
+
+    /// Find and parse the document defining the module of a fully qualified type
+    fn resolve_module_document(
+        search_dirs: &HashMap<PathBuf, HashSet<Url>>,
+        uri: &Url,
+        module_name: &str,
+        fqn: &Fqn,
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Option<(ParsedTree, String)> {
+        packages::resolve_import(
+            search_dirs,
+            uri,
+            module_name,
+            &fqn.module_path_parts(),
+            &|path| fs::exists(path).is_ok_and(|exists| exists),
+        )
+        .and_then(|target_uri| get_or_parse_document(&target_uri))
+    }
+
+    fn unknown_type_diagnostic(scoped_name: &Node, message: String) -> Diagnostic {
+        Diagnostic {
+            range: ts_lsp_interop::ts_to_lsp_range(&scoped_name.range()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(String::from("unknown-type"))),
+            message,
+            ..Default::default()
+        }
+    }
+}
+
+/// Whether a document is an ADL module rather than a companion annotation file (e.g. `.adl-java`)
+pub fn is_adl_file(uri: &Url) -> bool {
+    Path::new(uri.path())
+        .extension()
+        .is_some_and(|ext| ext == "adl")
+}
+
+/// Identify a set of diagnostics so that clients pulling them again can be told nothing has changed
+pub fn diagnostics_result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diagnostics)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
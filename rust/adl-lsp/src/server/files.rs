@@ -0,0 +1,223 @@
+use std::collections::HashMap;
+use std::ops::ControlFlow;
+use std::path::{Path, PathBuf};
+
+use async_lsp::{Error, LanguageClient, ResponseError};
+use lsp_types::{
+    CreateFilesParams, DeleteFilesParams, FileRename, PublishDiagnosticsParams, RenameFilesParams,
+    Url, WorkspaceEdit,
+};
+use tracing::{debug, warn};
+
+use crate::server::Server;
+use crate::server::packages;
+use crate::server::rename::is_valid_identifier;
+
+impl Server {
+    /// Rewrite the module declaration of each moved file to match its new location, along with
+    /// every import and fully qualified reference to the module across the workspace
+    pub fn handle_will_rename_files(
+        &mut self,
+        params: RenameFilesParams,
+    ) -> Result<Option<WorkspaceEdit>, ResponseError> {
+        let mut changes = HashMap::<Url, Vec<_>>::new();
+
+        for (old_uri, new_uri) in self.expand_file_renames(&params.files) {
+            let Some((tree, content)) = self.get_or_parse_document_with_content(&old_uri) else {
+                continue;
+            };
+            let Some(module_name) = tree.find_module_name(content.as_bytes()) else {
+                continue;
+            };
+            let Some(new_module_name) = self.module_name_for_new_location(&old_uri, &new_uri)
+            else {
+                warn!("could not find a module name for {}", new_uri);
+                continue;
+            };
+            if module_name == new_module_name {
+                continue;
+            }
+
+            debug!("renaming module {} to {}", module_name, new_module_name);
+
+            // companion annotation files (e.g. `.adl-java`) only need their own declaration updated
+            let is_adl_file = Path::new(old_uri.path())
+                .extension()
+                .is_some_and(|ext| ext == "adl");
+            let document_uris = if is_adl_file {
+                self.state.get_document_uris()
+            } else {
+                vec![old_uri.clone()]
+            };
+
+            for document_uri in document_uris {
+                let Some((document_tree, document_content)) =
+                    self.state.get_document_tree_and_content(&document_uri)
+                else {
+                    continue;
+                };
+                let edits = document_tree.module_rename_edits(
+                    module_name,
+                    &new_module_name,
+                    document_content.as_bytes(),
+                );
+                if !edits.is_empty() {
+                    changes.entry(document_uri).or_default().extend(edits);
+                }
+            }
+        }
+
+        if changes.is_empty() {
+            return Ok(None);
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    /// Move the moved files to their new location in the index and re-resolve the files that imported them
+    pub fn handle_did_rename_files(
+        &mut self,
+        params: RenameFilesParams,
+    ) -> ControlFlow<Result<(), Error>> {
+        let mut dependents = vec![];
+
+        for (old_uri, new_uri) in self.expand_file_renames(&params.files) {
+            dependents.extend(self.documents_defining_same_module(&old_uri));
+            dependents.extend(self.state.remove_document(&old_uri));
+            self.clear_diagnostics(&old_uri);
+
+            match std::fs::read_to_string(new_uri.path()) {
+                Ok(contents) => self.ingest_document(&new_uri, contents),
+                Err(e) => warn!("failed to read renamed file {}: {}", new_uri, e),
+            }
+        }
+
+        dependents.sort();
+        dependents.dedup();
+        self.refresh_documents(dependents);
+
+        ControlFlow::Continue(())
+    }
+
+    /// Index newly created files and re-resolve the files that import their modules
+    pub fn handle_did_create_files(
+        &mut self,
+        params: CreateFilesParams,
+    ) -> ControlFlow<Result<(), Error>> {
+        for file in params.files {
+            let Ok(uri) = Url::parse(&file.uri) else {
+                continue;
+            };
+            // imports of the new module could not be resolved until now
+            match std::fs::read_to_string(uri.path()) {
+                Ok(contents) => self.ingest_document_and_dependents(&uri, contents),
+                Err(e) => warn!("failed to read created file {}: {}", uri, e),
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Remove deleted files (or every file in a deleted folder) from the index and re-resolve the files that imported them
+    pub fn handle_did_delete_files(
+        &mut self,
+        params: DeleteFilesParams,
+    ) -> ControlFlow<Result<(), Error>> {
+        let document_uris = self.state.get_document_uris();
+        let mut dependents = vec![];
+
+        let deleted_uris: Vec<Url> = params
+            .files
+            .iter()
+            .filter_map(|file| Url::parse(&file.uri).ok())
+            .flat_map(|uri| {
+                if document_uris.contains(&uri) {
+                    return vec![uri];
+                }
+                let folder = PathBuf::from(uri.path());
+                document_uris
+                    .iter()
+                    .filter(|document_uri| Path::new(document_uri.path()).starts_with(&folder))
+                    .cloned()
+                    .collect()
+            })
+            .collect();
+
+        for uri in &deleted_uris {
+            dependents.extend(self.documents_defining_same_module(uri));
+            dependents.extend(self.state.remove_document(uri));
+            self.clear_diagnostics(uri);
+        }
+
+        dependents.retain(|dependent| !deleted_uris.contains(dependent));
+        dependents.sort();
+        dependents.dedup();
+        self.refresh_documents(dependents);
+
+        ControlFlow::Continue(())
+    }
+
+    /// Pair up the old and new locations of every known document affected by a rename,
+    /// expanding renamed folders into the documents they contain
+    fn expand_file_renames(&self, renames: &[FileRename]) -> Vec<(Url, Url)> {
+        let document_uris = self.state.get_document_uris();
+
+        renames
+            .iter()
+            .filter_map(|rename| {
+                let old_uri = Url::parse(&rename.old_uri).ok()?;
+                let new_uri = Url::parse(&rename.new_uri).ok()?;
+                Some((old_uri, new_uri))
+            })
+            .flat_map(|(old_uri, new_uri)| {
+                if document_uris.contains(&old_uri) {
+                    return vec![(old_uri, new_uri)];
+                }
+
+                let old_folder = PathBuf::from(old_uri.path());
+                let new_folder = PathBuf::from(new_uri.path());
+                document_uris
+                    .iter()
+                    .filter_map(|uri| {
+                        let relative_path = Path::new(uri.path()).strip_prefix(&old_folder).ok()?;
+                        let moved_uri = Url::from_file_path(new_folder.join(relative_path)).ok()?;
+                        Some((uri.clone(), moved_uri))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The module name a file must declare once it has moved, provided it stays within a package root
+    fn module_name_for_new_location(&self, old_uri: &Url, new_uri: &Url) -> Option<String> {
+        let new_path = PathBuf::from(new_uri.path());
+
+        let package_root = self
+            .state
+            .get_package_root(old_uri)
+            .or_else(|| packages::find_package_root_by_marker(old_uri.path()))
+            .into_iter()
+            .chain(self.config.search_dirs.iter().cloned())
+            .find(|root| new_path.starts_with(root))
+            .or_else(|| {
+                new_path
+                    .parent()
+                    .and_then(packages::find_package_root_by_marker)
+            })?;
+
+        packages::module_name_for_path(package_root, &new_path)
+            .filter(|module_name| module_name.split('.').all(is_valid_identifier))
+    }
+
+    /// Clear the diagnostics of a document that no longer exists
+    fn clear_diagnostics(&mut self, uri: &Url) {
+        let _res = self.client.publish_diagnostics(PublishDiagnosticsParams {
+            uri: uri.clone(),
+            diagnostics: vec![],
+            version: None,
+        });
+    }
+}
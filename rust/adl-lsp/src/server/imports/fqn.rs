@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Fqn {
     module_name: String,
     type_name: String,
@@ -12,6 +12,14 @@ impl Fqn {
         }
     }
 
+    pub fn module_name(&self) -> &str {
+        &self.module_name
+    }
+
+    pub fn type_name(&self) -> &str {
+        &self.type_name
+    }
+
     /// Returns the module path parts as a vector of strings
     /// e.g. "adlc.package" -> ["adlc", "package"]
     ///
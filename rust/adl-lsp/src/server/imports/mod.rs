@@ -1,14 +1,16 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
 
-use async_lsp::lsp_types::Url;
+use async_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Url};
 use tracing::{debug, trace};
+use tree_sitter::Node;
 
 use crate::node::{AdlImportDeclaration, AdlModuleDefinition, NodeKind};
 use crate::parser::ParsedTree;
 use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
 use crate::server::packages;
 
 mod fqn;
@@ -18,8 +20,9 @@ pub use fqn::Fqn;
 #[derive(Debug, Clone, Default)]
 pub struct ImportsCache {
     /// Maps FQN -> target_uri where the symbol is defined
-    /// Since ADL workspaces cannot have duplicate symbol definitions,
-    /// each identifier maps to exactly one location
+    /// A valid ADL workspace has exactly one definition of each symbol, but when a module is
+    /// duplicated (e.g. vendored into a dependency) the first file to define it is kept here
+    /// and the duplicate is reported on the module declaration
     definition_locations: Arc<RwLock<HashMap<Fqn, Url>>>,
 
     /// Maps source_uri -> set of all FQNs it imports
@@ -47,6 +50,16 @@ impl ImportsCache {
             .cloned()
     }
 
+    /// Lookup (from the cache) every type defined in the workspace
+    pub fn lookup_all_definitions(&self) -> Vec<(Fqn, Url)> {
+        self.definition_locations
+            .read()
+            .expect("poisoned")
+            .iter()
+            .map(|(fqn, uri)| (fqn.clone(), uri.clone()))
+            .collect()
+    }
+
     /// Lookup (from the cache) all files that import a specific type
     pub fn lookup_files_that_import(&self, fqn: &Fqn) -> Vec<Url> {
         let imported_symbols = self.imported_symbols.read().expect("poisoned");
@@ -62,21 +75,95 @@ impl ImportsCache {
             .collect()
     }
 
+    /// Lookup (from the cache) all files that import at least one type defined by a target file
+    pub fn lookup_files_that_import_from(&self, target_uri: &Url) -> Vec<Url> {
+        let definition_locations = self.definition_locations.read().expect("poisoned");
+        let imported_symbols = self.imported_symbols.read().expect("poisoned");
+        imported_symbols
+            .iter()
+            .filter(|(uri, symbols)| {
+                *uri != target_uri
+                    && symbols
+                        .iter()
+                        .any(|fqn| definition_locations.get(fqn) == Some(target_uri))
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    /// Remove every import and definition registered for a file that no longer exists
+    pub fn remove_source(&self, source_uri: &Url) {
+        self.clear_source_caches(source_uri);
+    }
+
+    /// Lookup (from the cache) all types that a file imports, including those expanded from star imports
+    pub fn lookup_imported_symbols(&self, source_uri: &Url) -> Vec<Fqn> {
+        self.imported_symbols
+            .read()
+            .expect("poisoned")
+            .get(source_uri)
+            .map(|symbols| symbols.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Lookup (from the cache) every type that an unqualified name could refer to in a document.
+    ///
+    /// An explicitly imported name refers to the explicit import alone, otherwise the name could be a
+    /// local definition or any type brought into scope by a star import, and more than one makes it ambiguous.
+    pub fn lookup_unqualified_name(
+        &self,
+        source_uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_name: &str,
+    ) -> Vec<Fqn> {
+        let Some(module_name) = tree.find_module_name(content) else {
+            return vec![];
+        };
+        let imports: Vec<AdlImportDeclaration> = tree
+            .find_all_nodes(NodeKind::is_import_declaration)
+            .into_iter()
+            .filter_map(AdlImportDeclaration::try_new)
+            .collect();
+        let imported_symbols = self.lookup_imported_symbols(source_uri);
+
+        let explicitly_importing_modules: Vec<&str> = imports
+            .iter()
+            .filter(|import| import.imported_type_name(content) == Some(type_name))
+            .map(|import| import.module_name(content))
+            .collect();
+        let star_imported_modules: Vec<&str> = imports
+            .iter()
+            .filter(|import| import.imported_type_name(content).is_none())
+            .map(|import| import.module_name(content))
+            .collect();
+        let modules = if explicitly_importing_modules.is_empty() {
+            &star_imported_modules
+        } else {
+            &explicitly_importing_modules
+        };
+
+        let mut candidates: BTreeSet<Fqn> = imported_symbols
+            .into_iter()
+            .filter(|fqn| fqn.type_name() == type_name && modules.contains(&fqn.module_name()))
+            .collect();
+        if explicitly_importing_modules.is_empty()
+            && tree.find_local_type_names(content).contains(&type_name)
+        {
+            candidates.insert(Fqn::from_module_name_and_type_name(module_name, type_name));
+        }
+        candidates.into_iter().collect()
+    }
+
     /// Add a validated import to the table, registering its import and definition
     fn register_import(&self, source_uri: &Url, fqn: &Fqn, target_uri: &Url) {
-        let mut definition_locations = self.definition_locations.write().expect("poisoned");
-        let mut imported_symbols = self.imported_symbols.write().expect("poisoned");
-        let mut defined_symbols = self.defined_symbols.write().expect("poisoned");
-
-        definition_locations.insert(fqn.clone(), target_uri.clone());
-        imported_symbols
+        self.imported_symbols
+            .write()
+            .expect("poisoned")
             .entry(source_uri.clone())
             .or_default()
             .insert(fqn.clone());
-        defined_symbols
-            .entry(target_uri.clone())
-            .or_default()
-            .insert(fqn.clone());
+        self.register_definition(target_uri, fqn);
     }
 
     /// Add a definition to the table (for symbols defined in a file)
@@ -84,7 +171,17 @@ impl ImportsCache {
         let mut definition_locations = self.definition_locations.write().expect("poisoned");
         let mut defined_symbols = self.defined_symbols.write().expect("poisoned");
 
-        definition_locations.insert(fqn.clone(), source_uri.clone());
+        let is_defined_elsewhere = definition_locations.get(fqn).is_some_and(|uri| {
+            uri != source_uri
+                && defined_symbols
+                    .get(uri)
+                    .is_some_and(|symbols| symbols.contains(fqn))
+        });
+        if is_defined_elsewhere {
+            debug!("{:?} is already defined by another file", fqn);
+        } else {
+            definition_locations.insert(fqn.clone(), source_uri.clone());
+        }
         defined_symbols
             .entry(source_uri.clone())
             .or_default()
@@ -108,9 +205,26 @@ impl ImportsCache {
         // Remove the definition cache for this source
         let definitions = { definition_table.remove(source_uri).unwrap_or_default() };
 
-        // Remove the definition locations for all symbols in this source
+        // Remove the definition locations for all symbols in this source, falling back to any
+        // other file that defines the same symbol
         for identifier in definitions {
-            definition_locations.remove(&identifier);
+            if definition_locations
+                .get(&identifier)
+                .is_some_and(|uri| uri != source_uri)
+            {
+                continue;
+            }
+            match definition_table
+                .iter()
+                .find(|(_, symbols)| symbols.contains(&identifier))
+            {
+                Some((other_uri, _)) => {
+                    definition_locations.insert(identifier, other_uri.clone());
+                }
+                None => {
+                    definition_locations.remove(&identifier);
+                }
+            }
         }
     }
 }
@@ -123,15 +237,16 @@ pub trait ImportManager {
     /// Clear the imports cache
     fn clear_cache(&mut self);
 
-    /// Resolve imports from a document and populate the imports table
+    /// Resolve imports from a document and populate the imports table,
+    /// returning diagnostics for any imports that could not be resolved
     fn resolve_and_register_imports(
         &self,
         search_dirs: &HashMap<PathBuf, HashSet<Url>>,
         source_uri: &Url,
         tree: &ParsedTree,
         content: &[u8],
-        get_or_parse_document_tree: &mut impl FnMut(&Url) -> Option<ParsedTree>,
-    );
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic>;
 }
 
 impl ImportManager for ImportsCache {
@@ -145,15 +260,16 @@ impl ImportManager for ImportsCache {
         self.clear();
     }
 
-    /// Resolve imports from a document and populate the imports table
+    /// Resolve imports from a document and populate the imports table,
+    /// returning diagnostics for any imports that could not be resolved
     fn resolve_and_register_imports(
         &self,
         search_dirs: &HashMap<PathBuf, HashSet<Url>>,
         source_uri: &Url,
         source_tree: &ParsedTree,
         source_content: &[u8],
-        get_or_parse_document_tree: &mut impl FnMut(&Url) -> Option<ParsedTree>,
-    ) {
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Vec<Diagnostic> {
         trace!("resolving imports for document: {}", source_uri);
 
         // Check that this document defines a module
@@ -161,7 +277,7 @@ impl ImportManager for ImportsCache {
             if let Some(module_definition) = source_tree.find_module_definition() {
                 module_definition
             } else {
-                return;
+                return vec![];
             };
 
         // Clear existing imports for this source
@@ -176,16 +292,20 @@ impl ImportManager for ImportsCache {
             .into_iter()
             .filter_map(AdlImportDeclaration::try_new);
 
-        for import_node in import_nodes {
-            self.register_import_declaration(
-                search_dirs,
-                source_uri,
-                module_definition.module_name(source_content),
-                source_content,
-                &import_node,
-                get_or_parse_document_tree,
-            );
-        }
+        import_nodes
+            .filter_map(|import_node| {
+                self.register_import_declaration(
+                    search_dirs,
+                    source_uri,
+                    module_definition.module_name(source_content),
+                    source_content,
+                    &import_node,
+                    get_or_parse_document,
+                )
+                .err()
+                .map(|e| e.to_diagnostic(import_node.inner()))
+            })
+            .collect()
     }
 }
 
@@ -208,7 +328,7 @@ impl ImportsCache {
     /// `source_module` is the module name of the source document e.g. `common.http`
     /// `source_content` is the text content of the source document
     /// `import_node` is the import declaration node to process
-    /// `get_or_parse_document_tree` is a function that returns a parsed tree for a given document URI
+    /// `get_or_parse_document` is a function that returns a parsed tree and content for a given document URI
     fn register_import_declaration(
         &self,
         search_dirs: &HashMap<PathBuf, HashSet<Url>>,
@@ -216,8 +336,8 @@ impl ImportsCache {
         source_module: &str,
         source_content: &[u8],
         import_node: &AdlImportDeclaration,
-        get_or_parse_document_tree: &mut impl FnMut(&Url) -> Option<ParsedTree>,
-    ) {
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Result<(), ImportError> {
         match import_node {
             AdlImportDeclaration::FullyQualified(_) => {
                 // Extract imported FQN from the import declaration
@@ -227,7 +347,13 @@ impl ImportsCache {
                         .imported_type_name(source_content)
                         .expect(" expected FullyQualified import to have a type_name "),
                 );
-                self.resolve_fully_qualified_import(search_dirs, source_uri, source_module, &fqn);
+                self.resolve_fully_qualified_import(
+                    search_dirs,
+                    source_uri,
+                    source_module,
+                    &fqn,
+                    get_or_parse_document,
+                )
             }
             AdlImportDeclaration::StarImport(_) => {
                 let imported_module_path =
@@ -240,8 +366,8 @@ impl ImportsCache {
                     source_uri,
                     source_module,
                     &imported_module_path,
-                    get_or_parse_document_tree,
-                );
+                    get_or_parse_document,
+                )
             }
         }
     }
@@ -253,8 +379,8 @@ impl ImportsCache {
         source_uri: &Url,
         source_module: &str,
         imported_module_path: &Vec<&str>,
-        get_or_parse_document_tree: &mut impl FnMut(&Url) -> Option<ParsedTree>,
-    ) {
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Result<(), ImportError> {
         debug!("expanding star import from {:?}", imported_module_path);
 
         let imported_module = packages::resolve_import(
@@ -265,22 +391,24 @@ impl ImportsCache {
             &|path| fs::exists(path).is_ok_and(|exists| exists),
         );
 
-        if let Some(ref target_uri) = imported_module {
-            // Try to read the target module
-            if let Ok(target_content) = std::fs::read_to_string(target_uri.path()) {
-                // Get or parse the target tree
-                if let Some(target_tree) = get_or_parse_document_tree(target_uri) {
-                    // Find all type definitions in the target module
-                    let type_definitions =
-                        self.find_type_definitions(&target_tree, target_content.as_bytes());
-
-                    // Add each type definition as an imported symbol
-                    for ref type_name in type_definitions {
-                        self.register_import(source_uri, type_name, target_uri);
-                    }
-                }
-            }
+        // Get or parse the target module
+        let Some((target_uri, (target_tree, target_content))) = imported_module
+            .and_then(|target_uri| get_or_parse_document(&target_uri).map(|t| (target_uri, t)))
+        else {
+            return Err(ImportError::UnresolvedModule(
+                imported_module_path.join("."),
+            ));
+        };
+
+        // Find all type definitions in the target module
+        let type_definitions = self.find_type_definitions(&target_tree, target_content.as_bytes());
+
+        // Add each type definition as an imported symbol
+        for ref type_name in type_definitions {
+            self.register_import(source_uri, type_name, &target_uri);
         }
+
+        Ok(())
     }
 
     /// Find all type definitions in a parsed tree and return their FQNs
@@ -318,14 +446,15 @@ impl ImportsCache {
         type_definitions
     }
 
-    /// Resolve a fully-qualified import
+    /// Resolve a fully-qualified import, checking that the target module defines the imported type
     fn resolve_fully_qualified_import(
         &self,
         search_dirs: &HashMap<PathBuf, HashSet<Url>>,
         source_uri: &Url,
         source_module: &str,
         import: &Fqn,
-    ) {
+        get_or_parse_document: &mut impl FnMut(&Url) -> Option<(ParsedTree, String)>,
+    ) -> Result<(), ImportError> {
         debug!("resolving fully-qualified import: {:?}", import);
 
         // Resolve the module paths
@@ -337,23 +466,65 @@ impl ImportsCache {
             &|path| fs::exists(path).is_ok_and(|exists| exists),
         );
 
-        if let Some(ref target_uri) = possible_path {
-            // Only add the symbol if the target file actually exists
-            if std::fs::metadata(target_uri.path()).is_ok() {
-                trace!(
-                    "target file exists, adding to imports table: {}",
-                    target_uri.path()
-                );
-                // TODO(high): check if the target file actually contains the definition
-                // right now the LSP blindly trust the import and assume that the target file defines it
-                // here's an opportunity to publish diagnostics for invalid imports
-                self.register_import(source_uri, import, target_uri);
-            } else {
-                trace!(
-                    "target file does not exist, skipping: {}",
-                    target_uri.path()
-                );
+        // Only add the symbol if the target file actually exists
+        let Some((target_uri, (target_tree, target_content))) = possible_path
+            .and_then(|target_uri| get_or_parse_document(&target_uri).map(|t| (target_uri, t)))
+        else {
+            trace!("target module could not be resolved: {:?}", import);
+            return Err(ImportError::UnresolvedModule(
+                import.module_name().to_string(),
+            ));
+        };
+
+        if !self
+            .find_type_definitions(&target_tree, target_content.as_bytes())
+            .contains(import)
+        {
+            trace!(
+                "target file does not define the import, skipping: {}",
+                target_uri.path()
+            );
+            return Err(ImportError::UnknownType(import.clone()));
+        }
+
+        trace!(
+            "target file defines the import, adding to imports table: {}",
+            target_uri.path()
+        );
+        self.register_import(source_uri, import, &target_uri);
+
+        Ok(())
+    }
+}
+
+/// The reasons an import declaration could not be resolved
+#[derive(Debug)]
+enum ImportError {
+    /// No file for the module was found in any package root
+    UnresolvedModule(String),
+    /// The module was found but does not define the imported type
+    UnknownType(Fqn),
+}
+
+impl ImportError {
+    fn to_diagnostic(&self, import_declaration: &Node) -> Diagnostic {
+        let message = match self {
+            ImportError::UnresolvedModule(module_name) => {
+                format!("cannot find module `{}`", module_name)
             }
+            ImportError::UnknownType(fqn) => format!(
+                "module `{}` has no definition named `{}`",
+                fqn.module_name(),
+                fqn.type_name()
+            ),
+        };
+
+        Diagnostic {
+            range: ts_lsp_interop::ts_to_lsp_range(&import_declaration.range()),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: Some(NumberOrString::String(String::from("unresolved-import"))),
+            message,
+            ..Default::default()
         }
     }
 }
@@ -6,26 +6,36 @@ use std::sync::{Arc, Mutex};
 use async_lsp::router::Router;
 use async_lsp::{ClientSocket, Error, ErrorCode, ResponseError};
 use lsp_types::{
-    DiagnosticOptions, DiagnosticServerCapabilities, DidChangeConfigurationParams,
+    CodeActionKind, CodeActionOptions, CodeActionProviderCapability, CompletionItem,
+    CompletionItemLabelDetails, CompletionOptions, CompletionParams, CompletionResponse,
+    Diagnostic, DiagnosticOptions, DiagnosticServerCapabilities, DidChangeConfigurationParams,
     DidChangeTextDocumentParams, DidCloseTextDocumentParams, DidOpenTextDocumentParams,
     DidSaveTextDocumentParams, DocumentDiagnosticParams, DocumentDiagnosticReport,
     DocumentDiagnosticReportResult, DocumentSymbolParams, DocumentSymbolResponse,
-    FileOperationFilter, FileOperationPattern, FileOperationPatternKind,
+    ExecuteCommandOptions, FileOperationFilter, FileOperationPattern, FileOperationPatternKind,
     FileOperationRegistrationOptions, FullDocumentDiagnosticReport, GotoDefinitionParams,
     GotoDefinitionResponse, Hover, HoverContents, HoverParams, HoverProviderCapability,
-    InitializeParams, InitializeResult, Location, OneOf, ReferenceParams,
-    RelatedFullDocumentDiagnosticReport, SaveOptions, ServerCapabilities, ServerInfo,
-    TextDocumentSyncCapability, TextDocumentSyncOptions, TextDocumentSyncSaveOptions, Url,
-    WorkDoneProgressOptions, WorkspaceFileOperationsServerCapabilities,
-    WorkspaceServerCapabilities,
+    InitializeParams, InitializeResult, Location, OneOf, Position, ProgressToken, ReferenceParams,
+    RelatedFullDocumentDiagnosticReport, RelatedUnchangedDocumentDiagnosticReport, RenameOptions,
+    SaveOptions, ServerCapabilities, ServerInfo, TextDocumentSyncCapability,
+    TextDocumentSyncOptions, TextDocumentSyncSaveOptions, UnchangedDocumentDiagnosticReport, Url,
+    WorkDoneProgressOptions, WorkspaceDiagnosticParams, WorkspaceDiagnosticReport,
+    WorkspaceDiagnosticReportPartialResult, WorkspaceDiagnosticReportResult,
+    WorkspaceDocumentDiagnosticReport, WorkspaceFileOperationsServerCapabilities,
+    WorkspaceFullDocumentDiagnosticReport, WorkspaceServerCapabilities,
+    WorkspaceUnchangedDocumentDiagnosticReport,
 };
 use lsp_types::{notification, request};
+use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, trace, warn};
 
 use crate::node::NodeKind;
+use crate::parser::completion::Completion;
 use crate::parser::definition::{Definition, DefinitionLocation};
 use crate::parser::hover::Hover as HoverTrait;
+use crate::parser::lints::LintConfig;
 use crate::parser::references::References;
+use crate::parser::reserved::Target;
 use crate::parser::symbols::DocumentSymbols;
 use crate::parser::{AdlParser, ParsedTree};
 use crate::server::config::ServerConfig;
@@ -33,13 +43,33 @@ use crate::server::imports::Fqn;
 use crate::server::packages::AdlPackageDefinition;
 use crate::server::state::AdlLanguageServerState;
 
+mod code_actions;
 pub mod config;
+mod coverage;
+mod diagnostics;
+mod files;
 mod imports;
 mod packages;
+mod rename;
 mod state;
+mod typecheck;
 
 pub struct TickEvent;
 
+/// A `$/progress` notification carrying a partial result, which lsp-types only provides for work done progress
+enum PartialResultProgress {}
+
+impl notification::Notification for PartialResultProgress {
+    type Params = PartialResultProgressParams;
+    const METHOD: &'static str = "$/progress";
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PartialResultProgressParams {
+    token: ProgressToken,
+    value: WorkspaceDiagnosticReportPartialResult,
+}
+
 #[derive(Clone)]
 pub struct Server {
     client: ClientSocket,
@@ -76,14 +106,42 @@ impl From<Server> for Router<Server> {
                 let mut st = st.clone();
                 async move { st.handle_find_references(params) }
             })
+            .request::<request::Completion, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_completion(params) }
+            })
+            .request::<request::PrepareRenameRequest, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_prepare_rename(params) }
+            })
+            .request::<request::Rename, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_rename(params) }
+            })
+            .request::<request::CodeActionRequest, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_code_action(params) }
+            })
+            .request::<request::WillRenameFiles, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_will_rename_files(params) }
+            })
             .request::<request::DocumentDiagnosticRequest, _>(|st, params| {
                 let mut st = st.clone();
                 async move { st.handle_document_diagnostic_request(params) }
             })
+            .request::<request::WorkspaceDiagnosticRequest, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_workspace_diagnostic_request(params).await }
+            })
             .request::<request::DocumentSymbolRequest, _>(|st, params| {
                 let mut st = st.clone();
                 async move { st.handle_document_symbol_request(params) }
             })
+            .request::<request::ExecuteCommand, _>(|st, params| {
+                let mut st = st.clone();
+                async move { st.handle_execute_command(params) }
+            })
             .notification::<notification::DidOpenTextDocument>(|st, params| {
                 trace!("did open text document: {:?}", params);
                 st.handle_did_open_text_document(params)
@@ -100,6 +158,18 @@ impl From<Server> for Router<Server> {
                 trace!("did save text document: {:?}", params);
                 st.handle_did_save_text_document(params)
             })
+            .notification::<notification::DidCreateFiles>(|st, params| {
+                trace!("did create files: {:?}", params);
+                st.handle_did_create_files(params)
+            })
+            .notification::<notification::DidDeleteFiles>(|st, params| {
+                trace!("did delete files: {:?}", params);
+                st.handle_did_delete_files(params)
+            })
+            .notification::<notification::DidRenameFiles>(|st, params| {
+                trace!("did rename files: {:?}", params);
+                st.handle_did_rename_files(params)
+            })
             .notification::<notification::Exit>(|st, _| st.handle_exit())
             .notification::<notification::Initialized>(|_, _| ControlFlow::Continue(()))
             .notification::<notification::DidChangeConfiguration>(|st, params| {
@@ -125,8 +195,71 @@ impl Server {
 
     fn ingest_document(&mut self, uri: &Url, contents: String) {
         let mut parser = self.parser.lock().expect("poisoned");
-        self.state
-            .ingest_document(&mut self.client, &mut parser, uri, contents);
+        let lints = self.config.lints.read().expect("poisoned");
+        let targets = self.config.targets.read().expect("poisoned");
+        self.state.ingest_document(
+            &mut self.client,
+            &mut parser,
+            &lints,
+            &targets,
+            uri,
+            contents,
+        );
+    }
+
+    /// Ingest a document changed by the client, then re-resolve the documents that import its module
+    /// since their imports may no longer (or may now) resolve
+    fn ingest_document_and_dependents(&mut self, uri: &Url, contents: String) {
+        let mut module_names = vec![];
+        if let Some((tree, content)) = self.state.get_document_tree_and_content(uri) {
+            module_names.extend(tree.find_module_name(content.as_bytes()).map(String::from));
+        }
+
+        self.ingest_document(uri, contents);
+
+        if let Some((tree, content)) = self.state.get_document_tree_and_content(uri) {
+            module_names.extend(tree.find_module_name(content.as_bytes()).map(String::from));
+        }
+
+        // other files declaring the same module report it as a duplicate
+        let mut dependents: Vec<Url> = module_names
+            .iter()
+            .flat_map(|module_name| {
+                let mut documents = self.state.get_documents_referencing_module(module_name);
+                documents.extend(self.state.get_documents_defining_module(module_name));
+                documents
+            })
+            .filter(|dependent| dependent != uri)
+            .collect();
+        dependents.sort();
+        dependents.dedup();
+        self.refresh_documents(dependents);
+    }
+
+    /// Other ADL files that declare the same module as a document
+    fn documents_defining_same_module(&self, uri: &Url) -> Vec<Url> {
+        let Some((tree, content)) = self.state.get_document_tree_and_content(uri) else {
+            return vec![];
+        };
+        if !diagnostics::is_adl_file(uri) {
+            return vec![];
+        }
+        tree.find_module_name(content.as_bytes())
+            .map(|module_name| self.state.get_documents_defining_module(module_name))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|other_uri| other_uri != uri)
+            .collect()
+    }
+
+    /// Re-ingest documents whose imports may now resolve differently, republishing their diagnostics
+    fn refresh_documents(&mut self, uris: Vec<Url>) {
+        for uri in uris {
+            if let Some(contents) = self.state.get_document_content(&uri) {
+                debug!("refreshing dependent document: {}", uri);
+                self.ingest_document(&uri, contents);
+            }
+        }
     }
 
     /// Initialize the server by discovering and processing all ADL files in package roots
@@ -141,16 +274,35 @@ impl Server {
             &adl_file_to_package_root.len()
         );
 
-        adl_file_to_package_root
-            .iter()
-            .for_each(|(uri, _package_root)| {
-                if let Ok(contents) = std::fs::read_to_string(uri.path()) {
-                    debug!("preprocessing ADL file: {}", uri);
-                    self.ingest_document(uri, contents);
-                } else {
+        self.state
+            .register_package_files(adl_file_to_package_root.keys());
+
+        let contents: Vec<(Url, String)> = adl_file_to_package_root
+            .keys()
+            .filter_map(|uri| match std::fs::read_to_string(uri.path()) {
+                Ok(contents) => Some((uri.clone(), contents)),
+                Err(_) => {
                     error!("failed to read file: {}", uri.path());
+                    None
                 }
-            });
+            })
+            .collect();
+        self.state
+            .register_imports(&mut self.parser.lock().expect("poisoned"), &contents);
+
+        for (uri, contents) in contents {
+            debug!("preprocessing ADL file: {}", uri);
+            self.ingest_document(&uri, contents);
+        }
+
+        // documents ingested before a duplicate of their module was found don't report it yet
+        let duplicate_modules: Vec<Url> = self
+            .state
+            .get_document_uris()
+            .into_iter()
+            .filter(|uri| !self.documents_defining_same_module(uri).is_empty())
+            .collect();
+        self.refresh_documents(duplicate_modules);
 
         debug!("workspace initialization complete: files processed",);
     }
@@ -360,8 +512,23 @@ impl Server {
     /// Handle the `initialize` notification and respond with the server's capabilities.
     pub async fn handle_initialize(
         &mut self,
-        _params: InitializeParams,
+        params: InitializeParams,
     ) -> Result<InitializeResult, ResponseError> {
+        if let Some(lints) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("lints"))
+        {
+            self.update_lint_config(lints);
+        }
+        if let Some(targets) = params
+            .initialization_options
+            .as_ref()
+            .and_then(|options| options.get("targets"))
+        {
+            self.update_target_config(targets);
+        }
+
         let mut file_operation_filers = vec![FileOperationFilter {
             scheme: Some(String::from("file")),
             pattern: FileOperationPattern {
@@ -383,7 +550,21 @@ impl Server {
         }
 
         let file_registration_option = FileOperationRegistrationOptions {
-            filters: file_operation_filers,
+            filters: file_operation_filers.clone(),
+        };
+
+        // moving or deleting a folder affects every module inside it
+        let mut file_or_folder_filters = file_operation_filers;
+        file_or_folder_filters.push(FileOperationFilter {
+            scheme: Some(String::from("file")),
+            pattern: FileOperationPattern {
+                glob: String::from("**/*"),
+                matches: Some(FileOperationPatternKind::Folder),
+                ..Default::default()
+            },
+        });
+        let file_or_folder_registration_option = FileOperationRegistrationOptions {
+            filters: file_or_folder_filters,
         };
 
         let result = InitializeResult {
@@ -404,6 +585,24 @@ impl Server {
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 definition_provider: Some(OneOf::Left(true)),
                 references_provider: Some(OneOf::Left(true)),
+                completion_provider: Some(CompletionOptions {
+                    trigger_characters: Some(vec![String::from("<"), String::from(",")]),
+                    ..CompletionOptions::default()
+                }),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                })),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::QUICKFIX,
+                            CodeActionKind::SOURCE_ORGANIZE_IMPORTS,
+                            CodeActionKind::REFACTOR_REWRITE,
+                        ]),
+                        ..CodeActionOptions::default()
+                    },
+                )),
                 diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
                     DiagnosticOptions {
                         inter_file_dependencies: true,
@@ -411,21 +610,23 @@ impl Server {
                         work_done_progress_options: WorkDoneProgressOptions {
                             work_done_progress: None,
                         },
-                        // TODO(low): enable workspace diagnostics once we have a way to check module positioning
-                        // e.g. module a.b.c must live on disk as ../somepath/a/b/c.adl
-                        workspace_diagnostics: false,
+                        workspace_diagnostics: true,
                     },
                 )),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                execute_command_provider: Some(ExecuteCommandOptions {
+                    commands: vec![String::from(coverage::DOCUMENTATION_COVERAGE_COMMAND)],
+                    ..ExecuteCommandOptions::default()
+                }),
                 workspace: Some(WorkspaceServerCapabilities {
                     workspace_folders: None,
                     file_operations: Some(WorkspaceFileOperationsServerCapabilities {
                         did_create: Some(file_registration_option.clone()),
                         will_create: Some(file_registration_option.clone()),
-                        did_rename: Some(file_registration_option.clone()),
-                        will_rename: Some(file_registration_option.clone()),
-                        did_delete: Some(file_registration_option.clone()),
-                        will_delete: Some(file_registration_option.clone()),
+                        did_rename: Some(file_or_folder_registration_option.clone()),
+                        will_rename: Some(file_or_folder_registration_option.clone()),
+                        did_delete: Some(file_or_folder_registration_option.clone()),
+                        will_delete: Some(file_or_folder_registration_option),
                     }),
                 }),
                 ..ServerCapabilities::default()
@@ -582,6 +783,31 @@ impl Server {
             return Ok(None);
         }
 
+        // an ambiguous name could refer to any of the types that bring it into scope
+        let is_unqualified = node.parent().is_some_and(|p| p.named_child_count() == 1);
+        if let Some(fqns) = is_unqualified
+            .then(|| self.state.find_ambiguous_names(&uri, &tree, content))
+            .and_then(|mut ambiguous_names| ambiguous_names.remove(identifier))
+        {
+            debug!("{} is ambiguous between {:?}", identifier, fqns);
+            let locations: Vec<Location> = fqns
+                .iter()
+                .filter_map(|fqn| {
+                    self.resolve_import_from_table(fqn, |tree, contents| {
+                        match tree.definition(identifier, contents.as_bytes()) {
+                            Some(DefinitionLocation::Resolved(location)) => Ok(location),
+                            _ => Err(ResponseError::new(
+                                ErrorCode::INTERNAL_ERROR,
+                                "definition not found in target document",
+                            )),
+                        }
+                    })
+                    .ok()
+                })
+                .collect();
+            return Ok(Some(GotoDefinitionResponse::Array(locations)));
+        }
+
         // search for a definition location in the current file
         let definition_location = tree.definition(identifier, content);
 
@@ -713,31 +939,202 @@ impl Server {
         }
     }
 
+    pub fn handle_completion(
+        &mut self,
+        params: CompletionParams,
+    ) -> Result<Option<CompletionResponse>, ResponseError> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+
+        let Some((tree, content)) = self.get_or_parse_document_with_content(&uri) else {
+            return Ok(None);
+        };
+
+        if !tree.is_type_position(&position) {
+            return Ok(None);
+        }
+
+        let mut items = self.visible_type_items(&uri, &tree, &content, &position);
+        let visible: HashSet<String> = items.iter().map(|item| item.label.clone()).collect();
+
+        // Offer every other type in the workspace along with an edit that imports it
+        let module_name = tree
+            .find_module_name(content.as_bytes())
+            .unwrap_or_default();
+        for (fqn, target_uri) in self.state.get_all_definitions() {
+            if fqn.module_name() == module_name
+                || visible.contains(fqn.type_name())
+                || tree.has_import(fqn.module_name(), fqn.type_name(), content.as_bytes())
+            {
+                continue;
+            }
+            let Some((target_tree, target_content)) =
+                self.get_or_parse_document_with_content(&target_uri)
+            else {
+                continue;
+            };
+            let Some(mut item) =
+                target_tree.type_completion_item(fqn.type_name(), target_content.as_bytes())
+            else {
+                continue;
+            };
+            item.label_details = Some(CompletionItemLabelDetails {
+                detail: None,
+                description: Some(fqn.module_name().to_string()),
+            });
+            item.additional_text_edits = tree
+                .import_insertion_edit(fqn.module_name(), fqn.type_name(), content.as_bytes())
+                .map(|edit| vec![edit]);
+            items.push(item);
+        }
+
+        debug!("found {} completion items for {}", items.len(), uri);
+
+        Ok(Some(CompletionResponse::Array(items)))
+    }
+
+    /// Collect the types that can be referenced without qualification at a position in a document
+    fn visible_type_items(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &str,
+        position: &Position,
+    ) -> Vec<CompletionItem> {
+        // Types imported from other modules take precedence as their kind is known from the target document
+        let mut items = Vec::new();
+        for fqn in self.state.get_imported_types(uri) {
+            let Some(target_uri) = self.state.get_import_target(&fqn) else {
+                continue;
+            };
+            if let Some((target_tree, target_content)) =
+                self.get_or_parse_document_with_content(&target_uri)
+            {
+                items.extend(
+                    target_tree.type_completion_item(fqn.type_name(), target_content.as_bytes()),
+                );
+            }
+        }
+        items.extend(tree.type_completions(position, content.as_bytes()));
+
+        let mut seen = HashSet::new();
+        items.retain(|item| seen.insert(item.label.clone()));
+        items
+    }
+
     pub fn handle_document_diagnostic_request(
         &mut self,
         params: DocumentDiagnosticParams,
     ) -> Result<DocumentDiagnosticReportResult, ResponseError> {
         let uri = params.text_document.uri;
-        let Some((tree, content)) = self.get_or_parse_document_with_content(&uri) else {
+        let Some(diagnostics) = self.get_or_collect_diagnostics(&uri) else {
             return Err(ResponseError::new(
                 ErrorCode::INVALID_REQUEST,
                 "document not found",
             ));
         };
 
-        let diagnostics = tree.collect_diagnostics(&content);
-
-        Ok(DocumentDiagnosticReportResult::Report(
+        let result_id = diagnostics::diagnostics_result_id(&diagnostics);
+        let report = if params.previous_result_id.as_ref() == Some(&result_id) {
+            DocumentDiagnosticReport::Unchanged(RelatedUnchangedDocumentDiagnosticReport {
+                related_documents: None,
+                unchanged_document_diagnostic_report: UnchangedDocumentDiagnosticReport {
+                    result_id,
+                },
+            })
+        } else {
             DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
                 related_documents: None,
                 full_document_diagnostic_report: FullDocumentDiagnosticReport {
                     items: diagnostics,
-                    result_id: None,
+                    result_id: Some(result_id),
                 },
-            }),
+            })
+        };
+
+        Ok(DocumentDiagnosticReportResult::Report(report))
+    }
+
+    /// Report the diagnostics of every file in every package, not just the documents open in the client.
+    /// When the client asks for partial results, each package is streamed as soon as it has been checked
+    pub async fn handle_workspace_diagnostic_request(
+        &mut self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult, ResponseError> {
+        let previous_result_ids: HashMap<Url, String> = params
+            .previous_result_ids
+            .into_iter()
+            .map(|previous| (previous.uri, previous.value))
+            .collect();
+        let partial_result_token = params.partial_result_params.partial_result_token;
+
+        let mut items = vec![];
+        for (package_root, adl_files) in self.state.get_package_adl_files() {
+            debug!(
+                "collecting workspace diagnostics for package root: {}",
+                package_root.display()
+            );
+            let reports = adl_files.into_iter().filter_map(|uri| {
+                let diagnostics = self.get_or_collect_diagnostics(&uri)?;
+                let result_id = diagnostics::diagnostics_result_id(&diagnostics);
+                let report = if previous_result_ids.get(&uri) == Some(&result_id) {
+                    WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri,
+                            version: None,
+                            unchanged_document_diagnostic_report:
+                                UnchangedDocumentDiagnosticReport { result_id },
+                        },
+                    )
+                } else {
+                    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri,
+                        version: None,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            items: diagnostics,
+                            result_id: Some(result_id),
+                        },
+                    })
+                };
+                Some(report)
+            });
+
+            let Some(token) = &partial_result_token else {
+                items.extend(reports);
+                continue;
+            };
+            let reports: Vec<WorkspaceDocumentDiagnosticReport> = reports.collect();
+            if !reports.is_empty() {
+                let _res =
+                    self.client
+                        .notify::<PartialResultProgress>(PartialResultProgressParams {
+                            token: token.clone(),
+                            value: WorkspaceDiagnosticReportPartialResult { items: reports },
+                        });
+            }
+            // give the main loop a chance to write out the progress before the final response is ready
+            tokio::task::yield_now().await;
+        }
+
+        // once partial results have been sent the final response must not repeat them
+        Ok(WorkspaceDiagnosticReportResult::Report(
+            WorkspaceDiagnosticReport { items },
         ))
     }
 
+    /// The cached diagnostics of a document, checking it first if it has only been parsed while resolving imports
+    fn get_or_collect_diagnostics(&mut self, uri: &Url) -> Option<Vec<Diagnostic>> {
+        if let Some(diagnostics) = self.state.get_cached_diagnostics(uri) {
+            return Some(diagnostics);
+        }
+        let contents = self
+            .state
+            .get_document_content(uri)
+            .or_else(|| std::fs::read_to_string(uri.path()).ok())?;
+        self.ingest_document(uri, contents);
+        Some(self.state.get_cached_diagnostics(uri).unwrap_or_default())
+    }
+
     pub fn handle_document_symbol_request(
         &mut self,
         params: DocumentSymbolParams,
@@ -776,7 +1173,7 @@ impl Server {
     ) -> ControlFlow<Result<(), Error>> {
         let uri = params.text_document.uri;
         let contents = params.text_document.text;
-        self.ingest_document(&uri, contents);
+        self.ingest_document_and_dependents(&uri, contents);
         ControlFlow::Continue(())
     }
 
@@ -806,7 +1203,7 @@ impl Server {
     ) -> ControlFlow<Result<(), Error>> {
         let uri = params.text_document.uri;
         if let Some(contents) = params.text {
-            self.ingest_document(&uri, contents);
+            self.ingest_document_and_dependents(&uri, contents);
         } else {
             // NOTE: if there's no contents then this is unexpected but we could read the contents from the uri directly
             warn!("text file saved with no contents");
@@ -836,13 +1233,48 @@ impl Server {
                     .map(|v| PathBuf::from(v.as_str().unwrap()))
                     .collect()
             });
-        if let Ok(search_dirs) = search_dirs {
-            self.config.search_dirs = search_dirs;
-            self.state.clear_cache();
-            self.initialize_workspace();
+        let lints = params.settings.get("lints");
+        if let Some(lints) = lints {
+            self.update_lint_config(lints);
+        }
+        let targets = params.settings.get("targets");
+        if let Some(targets) = targets {
+            self.update_target_config(targets);
+        }
+
+        match search_dirs {
+            // the packages in the workspace may have changed, so discover them again
+            Ok(search_dirs) if search_dirs != self.config.search_dirs => {
+                self.config.search_dirs = search_dirs;
+                self.state.clear_cache();
+                self.initialize_workspace();
+            }
+            // only the checks changed, so check the documents again as they are in memory
+            _ if lints.is_some() || targets.is_some() => {
+                self.refresh_documents(self.state.get_document_uris())
+            }
+            _ => {}
         }
         ControlFlow::Continue(())
     }
+
+    /// Replace the lint rule levels with those from the client settings, keeping the previous levels
+    /// if the settings are invalid
+    fn update_lint_config(&self, lints: &serde_json::Value) {
+        match LintConfig::from_settings(lints) {
+            Ok(lints) => *self.config.lints.write().expect("poisoned") = lints,
+            Err(e) => error!("invalid lint configuration: {}", e),
+        }
+    }
+
+    /// Replace the languages checked for reserved words with those from the client settings, keeping
+    /// the previous targets if the settings are invalid
+    fn update_target_config(&self, targets: &serde_json::Value) {
+        match Target::from_settings(targets) {
+            Ok(targets) => *self.config.targets.write().expect("poisoned") = targets,
+            Err(e) => error!("invalid target configuration: {}", e),
+        }
+    }
 }
 
 // Events
@@ -856,6 +1288,9 @@ impl Server {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lsp_types::{
+        CodeAction, CodeActionOrCommand, CodeActionParams, DiagnosticSeverity, NumberOrString,
+    };
     use std::fs;
     use tempfile::TempDir;
 
@@ -995,4 +1430,1990 @@ mod tests {
         assert!(resolved_roots.contains(&normalized_package_b));
         assert_eq!(resolved_roots.len(), 2);
     }
+
+    /// Write the given ADL modules into a package root and index them with a fresh server
+    fn server_with_workspace(files: &[(&str, &str)]) -> (TempDir, Server) {
+        let (temp_dir, config) = workspace_config(files);
+        let mut server = Server::new(&ClientSocket::new_closed(), config);
+        server.initialize_workspace();
+        (temp_dir, server)
+    }
+
+    fn workspace_config(files: &[(&str, &str)]) -> (TempDir, ServerConfig) {
+        let temp_dir = TempDir::new().unwrap();
+        let package_root = packages::normalize_path(temp_dir.path());
+        fs::write(
+            package_root.join("adl-package.json"),
+            r#"{"name": "test", "dependencies": []}"#,
+        )
+        .unwrap();
+        for (path, contents) in files {
+            let path = package_root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        let config = ServerConfig::new(None, vec![package_root.to_string_lossy().to_string()]);
+        (temp_dir, config)
+    }
+
+    fn file_uri(temp_dir: &TempDir, path: &str) -> Url {
+        Url::from_file_path(packages::normalize_path(temp_dir.path()).join(path)).unwrap()
+    }
+
+    /// Send a request through the router, as the main loop does for a client
+    async fn router_request(
+        router: &mut Router<Server>,
+        method: &str,
+        params: serde_json::Value,
+    ) -> serde_json::Value {
+        let request = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "method": method,
+            "params": params,
+        }))
+        .unwrap();
+        tower::Service::call(router, request).await.unwrap()
+    }
+
+    /// Send a notification through the router, as the main loop does for a client
+    fn router_notify(router: &mut Router<Server>, method: &str, params: serde_json::Value) {
+        let notification = serde_json::from_value(serde_json::json!({
+            "method": method,
+            "params": params,
+        }))
+        .unwrap();
+        let _ = async_lsp::LspService::notify(router, notification);
+    }
+
+    #[test]
+    fn test_completion_with_auto_import() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User user;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n\n    union Role {\n        Void admin;\n    };\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let response = server
+            .handle_completion(CompletionParams {
+                text_document_position: lsp_types::TextDocumentPositionParams {
+                    text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(4, 10),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+                context: None,
+            })
+            .unwrap();
+        let Some(CompletionResponse::Array(items)) = response else {
+            panic!("expected completion items");
+        };
+
+        let user = items.iter().find(|i| i.label == "User").unwrap();
+        assert_eq!(user.kind, Some(lsp_types::CompletionItemKind::STRUCT));
+        assert_eq!(user.additional_text_edits, None);
+
+        let role = items.iter().find(|i| i.label == "Role").unwrap();
+        assert_eq!(role.kind, Some(lsp_types::CompletionItemKind::ENUM));
+        let edits = role.additional_text_edits.as_ref().unwrap();
+        assert_eq!(edits[0].new_text, "\n    import common.db.Role;");
+        assert_eq!(edits[0].range.start, Position::new(1, 26));
+    }
+
+    #[test]
+    fn test_import_quick_fix() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    struct Message {\n        User user;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: lsp_types::Range::new(Position::new(2, 9), Position::new(2, 9)),
+                context: Default::default(),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.title, "Import `common.db.User`");
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(edits[0].new_text, "\n    import common.db.User;\n");
+    }
+
+    #[test]
+    fn test_rename_type_across_workspace() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User author;\n        common.db.User reader;\n    };\n};\n",
+            ),
+            (
+                "app/other.adl",
+                "module app.other {\n    import common.db.*;\n\n    type Users = Vector<User>;\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n\n    annotation User::name Doc \"name\";\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let other_uri = file_uri(&temp_dir, "app/other.adl");
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+
+        let position = lsp_types::TextDocumentPositionParams {
+            text_document: lsp_types::TextDocumentIdentifier {
+                uri: main_uri.clone(),
+            },
+            position: Position::new(4, 10),
+        };
+        let prepared = server.handle_prepare_rename(position.clone()).unwrap();
+        assert!(matches!(
+            prepared,
+            Some(lsp_types::PrepareRenameResponse::RangeWithPlaceholder { ref placeholder, .. })
+                if placeholder == "User"
+        ));
+
+        // field names are renamed as fields rather than types
+        let field = lsp_types::TextDocumentPositionParams {
+            position: Position::new(4, 14),
+            ..position.clone()
+        };
+        assert!(matches!(
+            server.handle_prepare_rename(field).unwrap(),
+            Some(lsp_types::PrepareRenameResponse::RangeWithPlaceholder { ref placeholder, .. })
+                if placeholder == "author"
+        ));
+
+        let edit = server
+            .handle_rename(lsp_types::RenameParams {
+                text_document_position: position.clone(),
+                new_name: String::from("Person"),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes[&main_uri].len(), 3);
+        assert_eq!(changes[&other_uri].len(), 1);
+        assert_eq!(changes[&db_uri].len(), 2);
+        assert!(
+            changes
+                .values()
+                .flatten()
+                .all(|edit| edit.new_text == "Person")
+        );
+
+        let invalid = server.handle_rename(lsp_types::RenameParams {
+            text_document_position: position.clone(),
+            new_name: String::from("1Person"),
+            work_done_progress_params: Default::default(),
+        });
+        assert!(invalid.is_err());
+
+        // the importing module already defines a `Message`
+        let collision = server.handle_rename(lsp_types::RenameParams {
+            text_document_position: position,
+            new_name: String::from("Message"),
+            work_done_progress_params: Default::default(),
+        });
+        assert_eq!(
+            collision.unwrap_err().message,
+            "module app.main imports User but already has a type named Message in scope"
+        );
+    }
+
+    #[test]
+    fn test_rename_field_across_workspace() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User author = {\"name\": \"anon\", \"age\": 0};\n        Vector<User> readers = [{\"name\": \"a\"}];\n    };\n\n    annotation User::name Doc \"name\";\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n        Int32 age;\n    };\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+
+        // from the field reference of an annotation declaration
+        let position = lsp_types::TextDocumentPositionParams {
+            text_document: lsp_types::TextDocumentIdentifier {
+                uri: main_uri.clone(),
+            },
+            position: Position::new(8, 23),
+        };
+        let prepared = server.handle_prepare_rename(position.clone()).unwrap();
+        assert!(matches!(
+            prepared,
+            Some(lsp_types::PrepareRenameResponse::RangeWithPlaceholder { ref placeholder, .. })
+                if placeholder == "name"
+        ));
+
+        let edit = server
+            .handle_rename(lsp_types::RenameParams {
+                text_document_position: position.clone(),
+                new_name: String::from("fullName"),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes[&main_uri].len(), 3);
+        assert_eq!(changes[&db_uri].len(), 1);
+        assert_eq!(changes[&db_uri][0].new_text, "fullName");
+
+        // the new name must not clash with another field
+        let clash = server.handle_rename(lsp_types::RenameParams {
+            text_document_position: position,
+            new_name: String::from("age"),
+            work_done_progress_params: Default::default(),
+        });
+        assert!(clash.is_err());
+    }
+
+    #[test]
+    fn test_rename_field_json_keys() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "input/rename.adl",
+                include_str!("../parser/input/rename.adl"),
+            ),
+            (
+                "common/strings.adl",
+                "module common.strings {\n    newtype StringNE = String;\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "input/rename.adl");
+
+        // keys are renamed wherever the type is nested, including through type parameters and aliases
+        let edit = server
+            .handle_rename(lsp_types::RenameParams {
+                text_document_position: lsp_types::TextDocumentPositionParams {
+                    text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(4, 18),
+                },
+                new_name: String::from("fullName"),
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let mut edits = edit.changes.unwrap().remove(&uri).unwrap();
+        edits.sort_by_key(|edit| edit.range.start);
+        insta::assert_yaml_snapshot!(edits);
+    }
+
+    #[test]
+    fn test_rename_module_file() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User author;\n        common.db.Role role;\n    };\n};\n",
+            ),
+            (
+                "app/other.adl",
+                "module app.other {\n    import common.db.*;\n\n    type Users = Vector<User>;\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n\n    union Role {\n        Void admin;\n    };\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let other_uri = file_uri(&temp_dir, "app/other.adl");
+        let old_uri = file_uri(&temp_dir, "common/db.adl");
+        let new_uri = file_uri(&temp_dir, "shared/db.adl");
+
+        let rename = |old_uri: &Url, new_uri: &Url| lsp_types::RenameFilesParams {
+            files: vec![lsp_types::FileRename {
+                old_uri: old_uri.to_string(),
+                new_uri: new_uri.to_string(),
+            }],
+        };
+
+        // moving the containing folder renames the module too
+        let folder_edit = server
+            .handle_will_rename_files(rename(
+                &file_uri(&temp_dir, "common"),
+                &file_uri(&temp_dir, "shared"),
+            ))
+            .unwrap()
+            .unwrap();
+        assert_eq!(folder_edit.changes.unwrap().len(), 3);
+
+        let edit = server
+            .handle_will_rename_files(rename(&old_uri, &new_uri))
+            .unwrap()
+            .unwrap();
+        let changes = edit.changes.unwrap();
+        assert_eq!(changes[&old_uri].len(), 1);
+        assert_eq!(changes[&main_uri].len(), 2);
+        assert_eq!(changes[&other_uri].len(), 1);
+        assert!(
+            changes
+                .values()
+                .flatten()
+                .all(|edit| edit.new_text == "shared.db")
+        );
+
+        // the client applies the edit and moves the file
+        let moved_contents = fs::read_to_string(old_uri.path())
+            .unwrap()
+            .replace("common.db", "shared.db");
+        fs::create_dir_all(file_uri(&temp_dir, "shared").path()).unwrap();
+        fs::write(new_uri.path(), moved_contents).unwrap();
+        fs::remove_file(old_uri.path()).unwrap();
+
+        let _ = server.handle_did_rename_files(rename(&old_uri, &new_uri));
+
+        let user = Fqn::from_module_name_and_type_name("shared.db", "User");
+        assert_eq!(server.state.get_import_target(&user), Some(new_uri));
+        let old_user = Fqn::from_module_name_and_type_name("common.db", "User");
+        assert_eq!(server.state.get_import_target(&old_user), None);
+        assert!(!server.state.get_document_uris().contains(&old_uri));
+    }
+
+    #[test]
+    fn test_delete_and_create_module_file() {
+        let db_contents =
+            "module common.db {\n    struct User {\n        String name;\n    };\n};\n";
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User author;\n    };\n};\n",
+            ),
+            ("common/db.adl", db_contents),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+        let user = Fqn::from_module_name_and_type_name("common.db", "User");
+        assert_eq!(
+            server.state.get_imported_types(&main_uri),
+            vec![user.clone()]
+        );
+
+        // deleting the folder removes the module it contains
+        fs::remove_file(db_uri.path()).unwrap();
+        let _ = server.handle_did_delete_files(lsp_types::DeleteFilesParams {
+            files: vec![lsp_types::FileDelete {
+                uri: file_uri(&temp_dir, "common").to_string(),
+            }],
+        });
+        assert_eq!(server.state.get_import_target(&user), None);
+        assert!(!server.state.get_document_uris().contains(&db_uri));
+        assert!(server.state.get_imported_types(&main_uri).is_empty());
+
+        // recreating the module resolves the import again
+        fs::write(db_uri.path(), db_contents).unwrap();
+        let _ = server.handle_did_create_files(lsp_types::CreateFilesParams {
+            files: vec![lsp_types::FileCreate {
+                uri: db_uri.to_string(),
+            }],
+        });
+        assert_eq!(server.state.get_import_target(&user), Some(db_uri));
+        assert_eq!(server.state.get_imported_types(&main_uri), vec![user]);
+    }
+
+    #[test]
+    fn test_unresolved_import_diagnostics() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n    import common.db.Usr;\n    import common.nope.*;\n    import common.nope.Thing;\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+
+        let unresolved_imports = |server: &Server| {
+            server
+                .state
+                .get_cached_diagnostics(&main_uri)
+                .unwrap()
+                .into_iter()
+                .filter(|d| {
+                    d.code
+                        == Some(lsp_types::NumberOrString::String(
+                            "unresolved-import".into(),
+                        ))
+                })
+                .map(|d| (d.range.start.line, d.message))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            unresolved_imports(&server),
+            vec![
+                (
+                    2,
+                    String::from("module `common.db` has no definition named `Usr`")
+                ),
+                (3, String::from("cannot find module `common.nope`")),
+                (4, String::from("cannot find module `common.nope`")),
+            ]
+        );
+
+        // defining the missing type resolves the import in the importing document
+        let _ = server.handle_did_save_text_document(DidSaveTextDocumentParams {
+            text_document: lsp_types::TextDocumentIdentifier { uri: db_uri },
+            text: Some(String::from(
+                "module common.db {\n    struct User {\n        String name;\n    };\n\n    type Usr = User;\n};\n",
+            )),
+        });
+        assert_eq!(unresolved_imports(&server).len(), 2);
+    }
+
+    #[test]
+    fn test_unknown_type_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n    import common.strings.*;\n    import common.nope.*;\n\n    struct Message<T> {\n        User author;\n        StringNE body;\n        T extra;\n        Vector<Missing> missing;\n        common.db.Role role;\n        common.db.Rol typo;\n        common.nope.Thing nope;\n        app.main.Message<Int32> reply;\n        app.main.Other other;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n\n    union Role {\n        Void admin;\n    };\n};\n",
+            ),
+            (
+                "common/strings.adl",
+                "module common.strings {\n    newtype StringNE = String;\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+
+        // the unresolved star import does not hide the unknown type
+        let unknown_types = server
+            .state
+            .get_cached_diagnostics(&main_uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(lsp_types::NumberOrString::String("unknown-type".into())))
+            .map(|d| (d.range.start.line, d.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            unknown_types,
+            vec![
+                (9, String::from("cannot find type `Missing` in this scope")),
+                (
+                    11,
+                    String::from("module `common.db` has no definition named `Rol`")
+                ),
+                (12, String::from("cannot find module `common.nope`")),
+                (
+                    14,
+                    String::from("module `app.main` has no definition named `Other`")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unused_star_import_quick_fix() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.*;\n    import common.strings.*;\n\n    struct Message {\n        StringNE body;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+            (
+                "common/strings.adl",
+                "module common.strings {\n    newtype StringNE = String;\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let unused_imports: Vec<Diagnostic> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("unused-import".into())))
+            .collect();
+        assert_eq!(unused_imports.len(), 1);
+        assert_eq!(unused_imports[0].range.start, Position::new(1, 4));
+
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: unused_imports[0].range,
+                context: lsp_types::CodeActionContext {
+                    diagnostics: unused_imports,
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(actions.len(), 1);
+        let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+            panic!("expected a code action");
+        };
+        assert_eq!(action.title, "Remove unused import");
+        let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+        assert_eq!(
+            edits[0].range,
+            lsp_types::Range::new(Position::new(1, 0), Position::new(2, 0))
+        );
+    }
+
+    #[test]
+    fn test_ambiguous_star_imports() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.*;\n    import common.auth.*;\n\n    struct Message {\n        User author = {\"login\": \"a\"};\n        Vector<User> readers;\n        Role role;\n    };\n\n    struct Role {\n        String name;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+            (
+                "common/auth.adl",
+                "module common.auth {\n    struct User {\n        String login;\n    };\n\n    union Role {\n        Void admin;\n    };\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let ambiguities = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| {
+                d.code == Some(NumberOrString::String("ambiguous-type".into()))
+                    || d.code == Some(NumberOrString::String("ambiguous-import".into()))
+            })
+            .map(|d| {
+                (
+                    d.range.start.line,
+                    d.message,
+                    d.related_information.unwrap_or_default().len(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ambiguities,
+            vec![
+                (
+                    7,
+                    String::from(
+                        "`Role` is ambiguous, it could be any of `app.main.Role`, `common.auth.Role`"
+                    ),
+                    2
+                ),
+                (
+                    2,
+                    String::from(
+                        "`common.auth.*` imports `Role`, which is also in scope from `app.main`"
+                    ),
+                    1
+                ),
+                (
+                    5,
+                    String::from(
+                        "`User` is ambiguous, it could be any of `common.auth.User`, `common.db.User`"
+                    ),
+                    2
+                ),
+                (
+                    6,
+                    String::from(
+                        "`User` is ambiguous, it could be any of `common.auth.User`, `common.db.User`"
+                    ),
+                    2
+                ),
+                (
+                    1,
+                    String::from(
+                        "`common.db.*` imports `User`, which is also in scope from `common.auth`"
+                    ),
+                    1
+                ),
+                (
+                    2,
+                    String::from(
+                        "`common.auth.*` imports `User`, which is also in scope from `common.db`"
+                    ),
+                    1
+                ),
+            ]
+        );
+
+        // the default value of an ambiguous type is not checked against either candidate
+        assert!(
+            server
+                .state
+                .get_cached_diagnostics(&uri)
+                .unwrap()
+                .iter()
+                .all(|d| d.code != Some(NumberOrString::String("invalid-default".into())))
+        );
+
+        // goto definition offers every candidate instead of picking one
+        let definitions = server
+            .handle_goto_definition(GotoDefinitionParams {
+                text_document_position_params: lsp_types::TextDocumentPositionParams {
+                    text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                    position: Position::new(5, 9),
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap();
+        let Some(GotoDefinitionResponse::Array(locations)) = definitions else {
+            panic!("expected several definitions");
+        };
+        let mut definition_uris: Vec<Url> = locations.into_iter().map(|l| l.uri).collect();
+        definition_uris.sort();
+        assert_eq!(
+            definition_uris,
+            vec![
+                file_uri(&temp_dir, "common/auth.adl"),
+                file_uri(&temp_dir, "common/db.adl"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_module_diagnostics() {
+        let db_contents =
+            "module common.db {\n    struct User {\n        String name;\n    };\n};\n";
+        let (temp_dir, mut server) = server_with_workspace(&[
+            ("common/db.adl", db_contents),
+            ("vendor/common/db.adl", db_contents),
+            (
+                "common/db.adl-java",
+                "module common.db {\n    annotation User JavaClass \"User\";\n};\n",
+            ),
+        ]);
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+        let vendored_uri = file_uri(&temp_dir, "vendor/common/db.adl");
+
+        let duplicates = |server: &Server, uri: &Url| {
+            server
+                .state
+                .get_cached_diagnostics(uri)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::String("duplicate-definition".into())))
+                .collect::<Vec<_>>()
+        };
+
+        // both copies report each other, regardless of the order they were indexed in
+        let db_duplicates = duplicates(&server, &db_uri);
+        assert_eq!(db_duplicates.len(), 1);
+        assert_eq!(
+            db_duplicates[0].message,
+            format!(
+                "module `common.db` is also defined by {}",
+                vendored_uri.path()
+            )
+        );
+        assert_eq!(
+            db_duplicates[0].related_information.as_ref().unwrap()[0]
+                .location
+                .uri,
+            vendored_uri
+        );
+        assert_eq!(duplicates(&server, &vendored_uri).len(), 1);
+        assert!(duplicates(&server, &file_uri(&temp_dir, "common/db.adl-java")).is_empty());
+
+        // the type stays resolvable while either copy remains
+        let user = Fqn::from_module_name_and_type_name("common.db", "User");
+        fs::remove_file(vendored_uri.path()).unwrap();
+        let _ = server.handle_did_delete_files(lsp_types::DeleteFilesParams {
+            files: vec![lsp_types::FileDelete {
+                uri: vendored_uri.to_string(),
+            }],
+        });
+        assert!(duplicates(&server, &db_uri).is_empty());
+        assert_eq!(server.state.get_import_target(&user), Some(db_uri));
+    }
+
+    #[test]
+    fn test_duplicate_module_import_order() {
+        let db_contents =
+            "module common.db {\n    struct User {\n        String name;\n    };\n};\n";
+        let (temp_dir, config) = workspace_config(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n};\n",
+            ),
+            ("common/db.adl", db_contents),
+            ("vendor/common/db.adl", db_contents),
+        ]);
+        let mut server = Server::new(&ClientSocket::new_closed(), config);
+        let (adl_file_to_package_root, _) = server.discover_adl_files();
+        server
+            .state
+            .register_package_files(adl_file_to_package_root.keys());
+        let vendored_uri = file_uri(&temp_dir, "vendor/common/db.adl");
+
+        // the import resolves to the other copy after the vendored one has been defined
+        for path in ["vendor/common/db.adl", "app/main.adl"] {
+            let contents = fs::read_to_string(temp_dir.path().join(path)).unwrap();
+            server.ingest_document(&file_uri(&temp_dir, path), contents);
+        }
+
+        // the first definition is kept, as it is when the copies are ingested directly
+        let user = Fqn::from_module_name_and_type_name("common.db", "User");
+        assert_eq!(server.state.get_import_target(&user), Some(vendored_uri));
+    }
+
+    #[test]
+    fn test_type_arity_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.pairs.Pair;\n    import common.ids.*;\n\n    struct Box<T> {\n        T value;\n        T<Int32> wrong;\n    };\n\n    struct Message {\n        Pair<String, Int32> ok;\n        Pair<String> short;\n        Pair missing;\n        Box<Int32, Int32> long;\n        Id<Message> id;\n        common.pairs.Pair<Id, Id, Id> qualified;\n        Vector<Box> nested;\n    };\n};\n",
+            ),
+            (
+                "common/pairs.adl",
+                "module common.pairs {\n    struct Pair<A, B> {\n        A first;\n        B second;\n    };\n};\n",
+            ),
+            (
+                "common/ids.adl",
+                "module common.ids {\n    newtype Id = String;\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let arity_errors = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("type-arity".into())))
+            .map(|d| (d.range.start.line, d.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            arity_errors,
+            vec![
+                (6, String::from("`T` does not take type arguments")),
+                (
+                    11,
+                    String::from("`Pair` expects 2 type arguments, but 1 was given")
+                ),
+                (
+                    12,
+                    String::from("`Pair` expects 2 type arguments, but none were given")
+                ),
+                (
+                    13,
+                    String::from("`Box` expects 1 type argument, but 2 were given")
+                ),
+                (14, String::from("`Id` does not take type arguments")),
+                (
+                    15,
+                    String::from("`Pair` expects 2 type arguments, but 3 were given")
+                ),
+                (
+                    16,
+                    String::from("`Box` expects 1 type argument, but none were given")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_json_default_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                r#"module app.main {
+    import common.db.*;
+
+    struct Pair<A, B> {
+        A first;
+        B second;
+    };
+
+    newtype Code = Int32 = "x";
+
+    struct Defaults {
+        User owner = {"name": "a", "mail": "a@example.com"};
+        User bad = {"age": "x", "nick": 1};
+        Role admin = "admin";
+        Role custom = "custom";
+        Role both = {"admin": null, "custom": "x"};
+        Ids ids = [1, 256];
+        Int8 small = 128;
+        Int32 fraction = 1.5;
+        Nullable<String> nothing = null;
+        StringMap<Bool> flags = {"a": true, "b": 1};
+        Pair<Int32, String> pair = {"first": 1, "second": 2};
+        Score score = "high";
+        Vector<Pair<Role, Score>> nested = [{"first": {"custom": "x"}, "second": 2.5}];
+        Float huge = 1e39;
+        Double large = 1e39;
+    };
+};
+"#,
+            ),
+            (
+                "common/db.adl",
+                r#"module common.db {
+    struct User {
+        String name;
+        Int32 age = 0;
+        @SerializedName "mail"
+        String email = "";
+    };
+
+    union Role {
+        Void admin;
+        String custom;
+    };
+
+    type Ids = Vector<Word8>;
+
+    newtype Score = Float = 1.0;
+};
+"#,
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let invalid_defaults = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("invalid-default".into())))
+            .map(|d| (d.range.start.line, d.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            invalid_defaults,
+            vec![
+                (8, String::from("expected an integer, found a string")),
+                (12, String::from("expected an integer, found a string")),
+                (12, String::from("`User` has no field `nick`")),
+                (12, String::from("missing field `name` of `User`")),
+                (
+                    14,
+                    String::from("`Role` has no variant `custom` without a value")
+                ),
+                (
+                    15,
+                    String::from("expected exactly one variant of `Role`, found 2")
+                ),
+                (
+                    16,
+                    String::from("`256` is out of range for Word8, which holds 0 to 255")
+                ),
+                (
+                    17,
+                    String::from("`128` is out of range for Int8, which holds -128 to 127")
+                ),
+                (18, String::from("expected an integer, found `1.5`")),
+                (20, String::from("expected a boolean, found a number")),
+                (21, String::from("expected a string, found a number")),
+                (22, String::from("expected a number, found a string")),
+                (24, String::from("`1e39` is out of range for Float")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotation_value_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                r#"module app.main {
+    import common.db.DbTable;
+
+    @DbTable {"tableName": "users", "index": []}
+    struct User {
+        @SerializedName 42
+        String name;
+        @SerializedName "nickname"
+        String nick;
+    };
+
+    annotation User DbTable {"indexes": "name"};
+    annotation User::nick SerializedName "nick_name";
+};
+"#,
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct DbTable {\n        String tableName;\n        Vector<String> indexes = [];\n    };\n};\n",
+            ),
+            (
+                "sys/annotations.adl",
+                "module sys.annotations {\n    type SerializedName = String;\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        let invalid_values = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("invalid-annotation-value".into())))
+            .map(|d| (d.range, d.message))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            invalid_values,
+            vec![
+                (
+                    lsp_types::Range::new(Position::new(3, 36), Position::new(3, 47)),
+                    String::from("`DbTable` has no field `index`")
+                ),
+                (
+                    lsp_types::Range::new(Position::new(5, 24), Position::new(5, 26)),
+                    String::from("expected a string, found a number")
+                ),
+                (
+                    lsp_types::Range::new(Position::new(11, 40), Position::new(11, 46)),
+                    String::from("expected an array, found a string")
+                ),
+                (
+                    lsp_types::Range::new(Position::new(11, 28), Position::new(11, 47)),
+                    String::from("missing field `tableName` of `DbTable`")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_path_quick_fixes() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.db.User;\n\n    struct Message {\n        User author;\n    };\n};\n",
+            ),
+            (
+                "common/database.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+            (
+                "common/database.adl-java",
+                "module common.db {\n    annotation User JavaClass \"User\";\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let uri = file_uri(&temp_dir, "common/database.adl");
+        let java_uri = file_uri(&temp_dir, "common/database.adl-java");
+
+        let mismatches: Vec<Diagnostic> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("module-path-mismatch".into())))
+            .collect();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            mismatches[0].message,
+            "module `common.db` cannot be imported from this location, which must declare `module common.database`"
+        );
+        assert_eq!(
+            mismatches[0].range,
+            lsp_types::Range::new(Position::new(0, 7), Position::new(0, 16))
+        );
+
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: mismatches[0].range,
+                context: lsp_types::CodeActionContext {
+                    diagnostics: mismatches,
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let actions: Vec<CodeAction> = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect();
+        assert_eq!(actions.len(), 2);
+
+        // renaming the module updates the companion file and the importer
+        assert_eq!(actions[0].title, "Rename module to `common.database`");
+        let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let mut changed_uris: Vec<&Url> = changes.keys().collect();
+        changed_uris.sort();
+        assert_eq!(changed_uris, vec![&main_uri, &uri, &java_uri]);
+        assert_eq!(changes[&main_uri][0].new_text, "common.database");
+
+        // moving the file takes the companion file along
+        assert_eq!(actions[1].title, "Move file to `common/db.adl`");
+        let Some(lsp_types::DocumentChanges::Operations(operations)) =
+            &actions[1].edit.as_ref().unwrap().document_changes
+        else {
+            panic!("expected file operations");
+        };
+        let renames: Vec<(Url, Url)> = operations
+            .iter()
+            .filter_map(|operation| match operation {
+                lsp_types::DocumentChangeOperation::Op(lsp_types::ResourceOp::Rename(rename)) => {
+                    Some((rename.old_uri.clone(), rename.new_uri.clone()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            renames,
+            vec![
+                (uri, file_uri(&temp_dir, "common/db.adl")),
+                (java_uri, file_uri(&temp_dir, "common/db.adl-java")),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_workspace_diagnostics() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    struct Message {\n        Missing author;\n    };\n};\n",
+            ),
+            (
+                "common/db.adl",
+                "module common.db {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let db_uri = file_uri(&temp_dir, "common/db.adl");
+
+        async fn workspace_diagnostics(
+            server: &mut Server,
+            previous_result_ids: Vec<lsp_types::PreviousResultId>,
+        ) -> Vec<WorkspaceDocumentDiagnosticReport> {
+            let WorkspaceDiagnosticReportResult::Report(report) = server
+                .handle_workspace_diagnostic_request(WorkspaceDiagnosticParams {
+                    identifier: None,
+                    previous_result_ids,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                })
+                .await
+                .unwrap()
+            else {
+                panic!("expected a full workspace report");
+            };
+            report.items
+        }
+
+        // files that were never opened are reported too
+        let items = workspace_diagnostics(&mut server, vec![]).await;
+        assert_eq!(items.len(), 2);
+        let mut previous_result_ids = vec![];
+        for item in items {
+            let WorkspaceDocumentDiagnosticReport::Full(report) = item else {
+                panic!("expected a full report");
+            };
+            let expected_count = if report.uri == main_uri { 1 } else { 0 };
+            assert_eq!(
+                report.full_document_diagnostic_report.items.len(),
+                expected_count
+            );
+            previous_result_ids.push(lsp_types::PreviousResultId {
+                uri: report.uri,
+                value: report.full_document_diagnostic_report.result_id.unwrap(),
+            });
+        }
+
+        // nothing has changed since the last pull
+        let items = workspace_diagnostics(&mut server, previous_result_ids.clone()).await;
+        assert!(
+            items
+                .iter()
+                .all(|item| matches!(item, WorkspaceDocumentDiagnosticReport::Unchanged(_)))
+        );
+
+        // only the fixed file is reported again
+        let _ = server.handle_did_open_text_document(DidOpenTextDocumentParams {
+            text_document: lsp_types::TextDocumentItem {
+                uri: main_uri.clone(),
+                language_id: String::from("adl"),
+                version: 1,
+                text: String::from(
+                    "module app.main {\n    struct Message {\n        String author;\n    };\n};\n",
+                ),
+            },
+        });
+        let items = workspace_diagnostics(&mut server, previous_result_ids).await;
+        assert_eq!(items.len(), 2);
+        for item in items {
+            match item {
+                WorkspaceDocumentDiagnosticReport::Full(report) => {
+                    assert_eq!(report.uri, main_uri);
+                    assert!(report.full_document_diagnostic_report.items.is_empty());
+                }
+                WorkspaceDocumentDiagnosticReport::Unchanged(report) => {
+                    assert_eq!(report.uri, db_uri);
+                }
+            }
+        }
+
+        // streamed reports are not repeated in the final response
+        let items = server
+            .handle_workspace_diagnostic_request(WorkspaceDiagnosticParams {
+                identifier: None,
+                previous_result_ids: vec![],
+                work_done_progress_params: Default::default(),
+                partial_result_params: lsp_types::PartialResultParams {
+                    partial_result_token: Some(ProgressToken::String(String::from("token"))),
+                },
+            })
+            .await
+            .unwrap();
+        assert_eq!(
+            items,
+            WorkspaceDiagnosticReportResult::Report(WorkspaceDiagnosticReport { items: vec![] })
+        );
+    }
+
+    #[test]
+    fn test_cyclic_type_alias_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[
+            (
+                "app/a.adl",
+                "module app.a {\n    import app.b.B;\n\n    type A = B;\n    type List = Vector<List>;\n    type X = Y;\n    type Y = X;\n};\n",
+            ),
+            (
+                "app/b.adl",
+                "module app.b {\n    import app.a.A;\n\n    type B = A;\n};\n",
+            ),
+        ]);
+
+        let cycles = |path: &str| -> Vec<(u32, String)> {
+            server
+                .state
+                .get_cached_diagnostics(&file_uri(&temp_dir, path))
+                .unwrap()
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::String("cyclic-type-alias".into())))
+                .map(|d| (d.range.start.line, d.message))
+                .collect()
+        };
+
+        assert_eq!(
+            cycles("app/a.adl"),
+            vec![
+                (
+                    3,
+                    String::from(
+                        "type alias `A` is defined in terms of itself: `A` -> `app.b.B` -> `A`"
+                    )
+                ),
+                (
+                    5,
+                    String::from("type alias `X` is defined in terms of itself: `X` -> `Y` -> `X`")
+                ),
+                (
+                    6,
+                    String::from("type alias `Y` is defined in terms of itself: `Y` -> `X` -> `Y`")
+                ),
+            ]
+        );
+        assert_eq!(
+            cycles("app/b.adl"),
+            vec![(
+                3,
+                String::from(
+                    "type alias `B` is defined in terms of itself: `B` -> `app.a.A` -> `B`"
+                )
+            )]
+        );
+    }
+
+    #[test]
+    fn test_unconstructible_type_diagnostics() {
+        let (temp_dir, server) = server_with_workspace(&[(
+            "app/shapes.adl",
+            r#"module app.shapes {
+    struct Node { String name; Node next; };
+    struct List { String head; Nullable<List> tail; };
+    struct Tree { Vector<Tree> children; StringMap<Tree> named; };
+    struct Ping { Pong pong; };
+    struct Pong { Ping ping; };
+    struct Holder { Node node; };
+    union Loop { Loop again; Node node; };
+    union Choice { Node node; Void none; };
+    union Dead { Node node; };
+    type Alias = Wrapper;
+    struct Wrapper { Alias inner; };
+    struct Box<T> { T value; };
+    struct Boxed { Box<Boxed> inner; };
+};
+"#,
+        )]);
+
+        let mut unconstructible: Vec<(u32, String)> = server
+            .state
+            .get_cached_diagnostics(&file_uri(&temp_dir, "app/shapes.adl"))
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("unconstructible-type".into())))
+            .map(|d| (d.range.start.line, d.message))
+            .collect();
+        unconstructible.sort();
+
+        let expected: Vec<(u32, &str)> = vec![
+            (
+                1,
+                "struct `Node` can never be constructed because it always contains itself: `Node.next` -> `Node`",
+            ),
+            (
+                4,
+                "struct `Ping` can never be constructed because it always contains itself: `Ping.pong` -> `Pong.ping` -> `Ping`",
+            ),
+            (
+                5,
+                "struct `Pong` can never be constructed because it always contains itself: `Pong.ping` -> `Ping.pong` -> `Pong`",
+            ),
+            (
+                7,
+                "union `Loop` can never be constructed because none of its variants can be: `Loop.again` -> `Loop`",
+            ),
+            (
+                9,
+                "union `Dead` can never be constructed because none of its variants can be",
+            ),
+            (
+                11,
+                "struct `Wrapper` can never be constructed because it always contains itself: `Wrapper.inner` -> `Alias` -> `Wrapper`",
+            ),
+            (
+                13,
+                "struct `Boxed` can never be constructed because it always contains itself: `Boxed.inner` -> `Box.value` -> `Boxed`",
+            ),
+        ];
+        assert_eq!(
+            unconstructible,
+            expected
+                .into_iter()
+                .map(|(line, message)| (line, message.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_package_dependency_diagnostics() {
+        let package_json = r#"{"name": "app", "description": "café ☕", "dependencies": [{"localdir": "../vendor"}]}"#;
+        let (temp_dir, mut server) = server_with_workspace(&[
+            ("app/adl-package.json", package_json),
+            (
+                "app/main.adl",
+                "module main {\n    import shared.User;\n    import http.Request;\n    import local.Thing;\n};\n",
+            ),
+            (
+                "app/local.adl",
+                "module local {\n    struct Thing {};\n};\n",
+            ),
+            ("common/adl-package.json", r#"{"name": "common"}"#),
+            (
+                "common/shared.adl",
+                "module shared {\n    struct User {};\n};\n",
+            ),
+            (
+                "vendor/adl-package.json",
+                r#"{"name": "vendor", "dependencies": []}"#,
+            ),
+            (
+                "vendor/http.adl",
+                "module http {\n    struct Request {};\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        // only the import from the undeclared package is reported
+        let undeclared: Vec<Diagnostic> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("undeclared-dependency".into())))
+            .collect();
+        assert_eq!(undeclared.len(), 1);
+        assert_eq!(undeclared[0].range.start.line, 1);
+        assert_eq!(
+            undeclared[0].message,
+            "module `shared` belongs to package `common`, which is not a dependency of package `app`"
+        );
+
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: undeclared[0].range,
+                context: lsp_types::CodeActionContext {
+                    diagnostics: undeclared,
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let [CodeActionOrCommand::CodeAction(action)] = actions.as_slice() else {
+            panic!("expected a single code action, got {:?}", actions);
+        };
+        assert_eq!(
+            action.title,
+            "Add `../common` to the dependencies in adl-package.json"
+        );
+
+        let changes = action.edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let edits = &changes[&file_uri(&temp_dir, "app/adl-package.json")];
+        assert_eq!(edits.len(), 1);
+        // the edit counts utf-16 code units, so find the byte offset it points at
+        let mut utf16_offset = 0;
+        let offset = package_json
+            .char_indices()
+            .find(|(_, c)| {
+                utf16_offset += c.len_utf16();
+                utf16_offset > edits[0].range.start.character as usize
+            })
+            .map_or(package_json.len(), |(i, _)| i);
+        assert_eq!(
+            format!(
+                "{}{}{}",
+                &package_json[..offset],
+                edits[0].new_text,
+                &package_json[offset..]
+            ),
+            r#"{"name": "app", "description": "café ☕", "dependencies": [{"localdir": "../vendor"}, {"localdir": "../common"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_package_dependency_diagnostics_ingest_order() {
+        let (temp_dir, config) = workspace_config(&[
+            (
+                "app/adl-package.json",
+                r#"{"name": "app", "dependencies": []}"#,
+            ),
+            (
+                "app/main.adl",
+                "module main {\n    import shared.User;\n};\n",
+            ),
+            ("common/adl-package.json", r#"{"name": "common"}"#),
+            (
+                "common/shared.adl",
+                "module shared {\n    struct User {};\n};\n",
+            ),
+        ]);
+        let mut server = Server::new(&ClientSocket::new_closed(), config);
+        let (adl_file_to_package_root, _) = server.discover_adl_files();
+        server
+            .state
+            .register_package_files(adl_file_to_package_root.keys());
+
+        // the importer is checked before the package it imports from has been ingested
+        let uri = file_uri(&temp_dir, "app/main.adl");
+        for path in ["app/main.adl", "common/shared.adl"] {
+            let contents = fs::read_to_string(temp_dir.path().join(path)).unwrap();
+            server.ingest_document(&file_uri(&temp_dir, path), contents);
+        }
+
+        let undeclared: Vec<String> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("undeclared-dependency".into())))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            undeclared,
+            vec![
+                "module `shared` belongs to package `common`, which is not a dependency of package `app`"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_package_definition_cache() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/adl-package.json",
+                r#"{"name": "app", "dependencies": []}"#,
+            ),
+            (
+                "app/main.adl",
+                "module main {\n    import shared.User;\n    import sys.types.Pair;\n};\n",
+            ),
+            ("common/adl-package.json", r#"{"name": "common"}"#),
+            (
+                "common/shared.adl",
+                "module shared {\n    struct User {};\n};\n",
+            ),
+            ("stdlib/adl-package.json", r#"{"name": "stdlib"}"#),
+            (
+                "stdlib/sys/types.adl",
+                "module sys.types {\n    struct Pair<A, B> {\n        A v1;\n        B v2;\n    };\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+        let undeclared = |server: &Server| -> Vec<u32> {
+            server
+                .state
+                .get_cached_diagnostics(&uri)
+                .unwrap()
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::String("undeclared-dependency".into())))
+                .map(|d| d.range.start.line)
+                .collect()
+        };
+        // the standard library never needs to be declared
+        assert_eq!(undeclared(&server), vec![1]);
+
+        // the definition read on the first ingest is kept while the package is checked again
+        fs::write(
+            temp_dir.path().join("app/adl-package.json"),
+            r#"{"name": "app", "dependencies": [{"localdir": "../common"}]}"#,
+        )
+        .unwrap();
+        server.refresh_documents(vec![uri.clone()]);
+        assert_eq!(undeclared(&server), vec![1]);
+
+        // until the workspace is discovered again
+        server.state.clear_cache();
+        server.initialize_workspace();
+        assert_eq!(undeclared(&server), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_lint_configuration() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import app.other.*;\n\n    struct Message {\n        // adl-lsp: allow(unknown-type)\n        Missing author;\n        Unknown body;\n    };\n};\n",
+            ),
+            (
+                "app/other.adl",
+                "module app.other {\n    struct Other {};\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+        let reported = |server: &Server| -> Vec<(u32, String, Option<DiagnosticSeverity>)> {
+            server
+                .state
+                .get_cached_diagnostics(&uri)
+                .unwrap()
+                .into_iter()
+                .filter_map(|d| match d.code {
+                    Some(NumberOrString::String(code)) => {
+                        Some((d.range.start.line, code, d.severity))
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+
+        // the suppressed unknown type is not reported
+        assert_eq!(
+            reported(&server),
+            vec![
+                (
+                    6,
+                    String::from("unknown-type"),
+                    Some(DiagnosticSeverity::ERROR)
+                ),
+                (
+                    1,
+                    String::from("unused-import"),
+                    Some(DiagnosticSeverity::WARNING)
+                ),
+            ]
+        );
+
+        // unsaved changes are kept when only the lints change
+        let _ = server.handle_did_change_text_document(DidChangeTextDocumentParams {
+            text_document: lsp_types::VersionedTextDocumentIdentifier {
+                uri: uri.clone(),
+                version: 2,
+            },
+            content_changes: vec![lsp_types::TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: String::from(
+                    "module app.main {\n    import app.other.*;\n\n    struct Message {\n        // adl-lsp: allow(unknown-type)\n        Missing author;\n        Unknown body;\n        Unknown footer;\n    };\n};\n",
+                ),
+            }],
+        });
+        let _ = server.handle_did_change_configuration(DidChangeConfigurationParams {
+            settings: serde_json::json!({
+                "searchDirs": server.config.search_dirs,
+                "lints": {
+                    "unused-import": "off",
+                    "unknown-type": "warning",
+                },
+            }),
+        });
+        assert_eq!(
+            reported(&server),
+            vec![
+                (
+                    6,
+                    String::from("unknown-type"),
+                    Some(DiagnosticSeverity::WARNING)
+                ),
+                (
+                    7,
+                    String::from("unknown-type"),
+                    Some(DiagnosticSeverity::WARNING)
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lint_initialization_options() {
+        let (temp_dir, config) = workspace_config(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import app.other.*;\n};\n",
+            ),
+            (
+                "app/other.adl",
+                "module app.other {\n    struct Other {};\n};\n",
+            ),
+        ]);
+        let server = Server::new(&ClientSocket::new_closed(), config);
+        let mut router = Router::from(server.clone());
+        let uri = file_uri(&temp_dir, "app/main.adl");
+
+        router_request(
+            &mut router,
+            "initialize",
+            serde_json::json!({
+                "capabilities": {},
+                "initializationOptions": { "lints": { "unused-import": "off" } },
+            }),
+        )
+        .await;
+        // the documents the client opens later are checked with the same lints
+        router_notify(
+            &mut router,
+            "textDocument/didOpen",
+            serde_json::json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "adl",
+                    "version": 1,
+                    "text": "module app.main {\n    import app.other.*;\n\n    struct Message {};\n};\n",
+                },
+            }),
+        );
+
+        let codes: Vec<NumberOrString> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter_map(|d| d.code)
+            .collect();
+        assert_eq!(codes, vec![]);
+    }
+
+    #[tokio::test]
+    async fn test_target_initialization_options() {
+        let (temp_dir, config) = workspace_config(&[(
+            "common/query.adl",
+            "module common.query {\n    struct Query {\n        String class;\n    };\n};\n",
+        )]);
+        let server = Server::new(&ClientSocket::new_closed(), config);
+        let mut router = Router::from(server.clone());
+        let uri = file_uri(&temp_dir, "common/query.adl");
+
+        router_request(
+            &mut router,
+            "initialize",
+            serde_json::json!({
+                "capabilities": {},
+                "initializationOptions": { "targets": ["java"] },
+            }),
+        )
+        .await;
+        // the documents the client opens later are checked against the same targets
+        router_notify(
+            &mut router,
+            "textDocument/didOpen",
+            serde_json::json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": "adl",
+                    "version": 1,
+                    "text": "module common.query {\n    struct Query {\n        String class;\n        String match;\n    };\n};\n",
+                },
+            }),
+        );
+
+        let messages: Vec<String> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("reserved-word".into())))
+            .map(|d| d.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "field `class` is a reserved word in Java, so the generated code will not compile"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_naming_quick_fixes() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.user_types.*;\n\n    struct Message {\n        user_account Author;\n    };\n};\n",
+            ),
+            (
+                "common/user_types.adl",
+                "module common.user_types {\n    struct user_account<name_type> {\n        name_type first_name;\n    };\n};\n",
+            ),
+        ]);
+        let main_uri = file_uri(&temp_dir, "app/main.adl");
+        let uri = file_uri(&temp_dir, "common/user_types.adl");
+
+        let naming_diagnostics = |server: &Server, uri: &Url| -> Vec<Diagnostic> {
+            server
+                .state
+                .get_cached_diagnostics(uri)
+                .unwrap()
+                .into_iter()
+                .filter(|d| {
+                    matches!(&d.code, Some(NumberOrString::String(code)) if code.ends_with("-case"))
+                })
+                .collect()
+        };
+        let diagnostics = naming_diagnostics(&server, &uri);
+        let messages: Vec<&str> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "type `user_account` should be named in UpperCamelCase",
+                "type parameter `name_type` should be a single uppercase letter or UpperCamelCase",
+                "field `first_name` should be named in lowerCamelCase",
+            ]
+        );
+        assert_eq!(
+            naming_diagnostics(&server, &main_uri)[0].message,
+            "field `Author` should be named in lowerCamelCase"
+        );
+
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: diagnostics[0].range,
+                context: lsp_types::CodeActionContext {
+                    diagnostics,
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let actions: Vec<CodeAction> = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                CodeActionOrCommand::CodeAction(action) => Some(action),
+                CodeActionOrCommand::Command(_) => None,
+            })
+            .collect();
+        let titles: Vec<&str> = actions.iter().map(|a| a.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Rename to `UserAccount`",
+                "Rename to `NameType`",
+                "Rename to `firstName`",
+            ]
+        );
+
+        // renaming the type updates its uses in other files
+        let changes = actions[0].edit.as_ref().unwrap().changes.as_ref().unwrap();
+        assert_eq!(changes[&main_uri][0].new_text, "UserAccount");
+        assert_eq!(changes[&uri][0].new_text, "UserAccount");
+
+        // renaming the type parameter updates its uses within the definition
+        let changes = actions[1].edit.as_ref().unwrap().changes.as_ref().unwrap();
+        let ranges: Vec<lsp_types::Range> = changes[&uri].iter().map(|edit| edit.range).collect();
+        assert_eq!(
+            ranges,
+            vec![
+                lsp_types::Range::new(Position::new(1, 24), Position::new(1, 33)),
+                lsp_types::Range::new(Position::new(2, 8), Position::new(2, 17)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_module_name_case_quick_fix_moves_files() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    import common.UserTypes.User;\n\n    struct Message {\n        User author;\n    };\n};\n",
+            ),
+            (
+                "common/UserTypes.adl",
+                "module common.UserTypes {\n    struct User {\n        String name;\n    };\n};\n",
+            ),
+            (
+                "common/UserTypes.adl-java",
+                "module common.UserTypes {\n    annotation User JavaClass \"User\";\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "common/UserTypes.adl");
+
+        let diagnostics: Vec<Diagnostic> = server
+            .state
+            .get_cached_diagnostics(&uri)
+            .unwrap()
+            .into_iter()
+            .filter(|d| d.code == Some(NumberOrString::String("module-name-case".into())))
+            .collect();
+        let actions = server
+            .handle_code_action(CodeActionParams {
+                text_document: lsp_types::TextDocumentIdentifier { uri: uri.clone() },
+                range: diagnostics[0].range,
+                context: lsp_types::CodeActionContext {
+                    diagnostics,
+                    only: Some(vec![CodeActionKind::QUICKFIX]),
+                    ..Default::default()
+                },
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let [CodeActionOrCommand::CodeAction(action)] = actions.as_slice() else {
+            panic!("expected a single code action, got {:?}", actions);
+        };
+        assert_eq!(action.title, "Rename to `common.user_types`");
+
+        // apply the edits to the documents, then move the files
+        let Some(lsp_types::DocumentChanges::Operations(operations)) =
+            &action.edit.as_ref().unwrap().document_changes
+        else {
+            panic!("expected document changes");
+        };
+        for operation in operations {
+            match operation {
+                lsp_types::DocumentChangeOperation::Edit(edit) => {
+                    let path = edit.text_document.uri.path();
+                    let mut lines: Vec<String> = fs::read_to_string(path)
+                        .unwrap()
+                        .split('\n')
+                        .map(String::from)
+                        .collect();
+                    let mut edits: Vec<&lsp_types::TextEdit> = edit
+                        .edits
+                        .iter()
+                        .map(|edit| match edit {
+                            OneOf::Left(edit) => edit,
+                            OneOf::Right(annotated) => &annotated.text_edit,
+                        })
+                        .collect();
+                    edits.sort_by_key(|edit| std::cmp::Reverse(edit.range.start));
+                    for edit in edits {
+                        assert_eq!(edit.range.start.line, edit.range.end.line);
+                        let line = &mut lines[edit.range.start.line as usize];
+                        line.replace_range(
+                            edit.range.start.character as usize..edit.range.end.character as usize,
+                            &edit.new_text,
+                        );
+                    }
+                    fs::write(path, lines.join("\n")).unwrap();
+                }
+                lsp_types::DocumentChangeOperation::Op(lsp_types::ResourceOp::Rename(rename)) => {
+                    fs::rename(rename.old_uri.path(), rename.new_uri.path()).unwrap();
+                }
+                operation => panic!("unexpected operation {:?}", operation),
+            }
+        }
+        assert!(temp_dir.path().join("common/user_types.adl-java").exists());
+
+        // the renamed module is where its importer looks for it
+        let mut server = Server::new(&ClientSocket::new_closed(), server.config.clone());
+        server.initialize_workspace();
+        for path in ["app/main.adl", "common/user_types.adl"] {
+            let reported: Vec<Diagnostic> = server
+                .state
+                .get_cached_diagnostics(&file_uri(&temp_dir, path))
+                .unwrap();
+            assert_eq!(reported, vec![], "unexpected diagnostics in {}", path);
+        }
+    }
+
+    #[test]
+    fn test_reserved_word_diagnostics() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "common/query.adl",
+                "module common.query {\n    struct Query {\n        String match;\n        String class;\n    };\n};\n",
+            ),
+            (
+                "common/query.adl-rs",
+                "module common.query {\n    annotation Query::match RustFieldName \"match_\";\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "common/query.adl");
+        let messages = |server: &Server| -> Vec<String> {
+            server
+                .state
+                .get_cached_diagnostics(&uri)
+                .unwrap()
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::String("reserved-word".into())))
+                .map(|d| d.message)
+                .collect()
+        };
+
+        // the Rust annotation file renames `match`, which leaves only `class` to report
+        assert_eq!(
+            messages(&server),
+            vec![
+                "field `class` is a reserved word in Java, TypeScript, Haskell and C++, so the generated code will not compile"
+            ]
+        );
+
+        // only the configured targets are checked
+        let _ = server.handle_did_change_configuration(DidChangeConfigurationParams {
+            settings: serde_json::json!({ "targets": ["java", "rs"] }),
+        });
+        assert_eq!(
+            messages(&server),
+            vec![
+                "field `class` is a reserved word in Java, so the generated code will not compile"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_documentation_coverage() {
+        let (temp_dir, mut server) = server_with_workspace(&[
+            (
+                "app/main.adl",
+                "module app.main {\n    /// A message between users\n    struct Message {\n        String body;\n        @Doc \"Who sent the message\"\n        String author;\n    };\n};\n",
+            ),
+            (
+                "app/main.adl-java",
+                "module app.main {\n    annotation Message JavaClass \"Message\";\n};\n",
+            ),
+            (
+                "app/other.adl",
+                "module app.other {\n    union Other {\n        Void none;\n    };\n\n    annotation Other::none Doc \"Nothing\";\n};\n",
+            ),
+        ]);
+        let uri = file_uri(&temp_dir, "app/main.adl");
+        let missing_docs = |server: &Server| -> Vec<String> {
+            server
+                .state
+                .get_cached_diagnostics(&uri)
+                .unwrap()
+                .into_iter()
+                .filter(|d| d.code == Some(NumberOrString::String("missing-doc".into())))
+                .map(|d| d.message)
+                .collect()
+        };
+
+        // the lint is off until a workspace opts in
+        assert!(missing_docs(&server).is_empty());
+        let _ = server.handle_did_change_configuration(DidChangeConfigurationParams {
+            settings: serde_json::json!({ "lints": { "missing-doc": "warning" } }),
+        });
+        assert_eq!(
+            missing_docs(&server),
+            vec!["field `Message::body` has no `///` docstring or `@Doc` annotation"]
+        );
+
+        // the annotation file is not a module of its own
+        let report = server
+            .handle_execute_command(lsp_types::ExecuteCommandParams {
+                command: String::from("adl.documentationCoverage"),
+                arguments: vec![],
+                work_done_progress_params: Default::default(),
+            })
+            .unwrap()
+            .unwrap();
+        let package_root = packages::normalize_path(temp_dir.path());
+        assert_eq!(
+            report,
+            serde_json::json!({
+                "documented": 3,
+                "total": 5,
+                "packages": [{
+                    "packageRoot": package_root,
+                    "documented": 3,
+                    "total": 5,
+                    "modules": [
+                        {
+                            "moduleName": "app.main",
+                            "uri": uri,
+                            "documented": 2,
+                            "total": 3,
+                            "undocumented": ["Message::body"],
+                        },
+                        {
+                            "moduleName": "app.other",
+                            "uri": file_uri(&temp_dir, "app/other.adl"),
+                            "documented": 1,
+                            "total": 2,
+                            "undocumented": ["Other"],
+                        },
+                    ],
+                }],
+            })
+        );
+
+        assert!(
+            server
+                .handle_execute_command(lsp_types::ExecuteCommandParams {
+                    command: String::from("adl.unknown"),
+                    arguments: vec![],
+                    work_done_progress_params: Default::default(),
+                })
+                .is_err()
+        );
+    }
 }
@@ -6,7 +6,12 @@ use std::{
 };
 use tracing::{error, trace};
 
-#[derive(Debug, Deserialize)]
+/// The root module of the standard library, which the ADL compiler bundles with itself (e.g.
+/// `sys.types`, `sys.annotations`) so that its modules are found without being declared as a
+/// dependency of any package
+pub const STANDARD_LIBRARY_MODULE: &str = "sys";
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct AdlPackageRef {
     pub localdir: String,
 }
@@ -23,6 +28,25 @@ pub fn find_package_root_by_marker<T: AsRef<Path>>(path: T) -> Option<PathBuf> {
     }
 }
 
+/// The module name that a file must declare given its location under a package root
+/// e.g. `<root>/common/http.adl` must declare `module common.http`
+pub fn module_name_for_path<T: AsRef<Path>, U: AsRef<Path>>(
+    package_root: T,
+    path: U,
+) -> Option<String> {
+    let relative_path = path.as_ref().strip_prefix(package_root).ok()?;
+    let file_stem = relative_path.file_stem()?.to_str()?;
+
+    let mut parts = relative_path
+        .parent()?
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    parts.push(file_stem);
+
+    Some(parts.join("."))
+}
+
 /// Resolve a dependency path, handling both relative and absolute paths
 pub fn resolve_dependency_path<T: AsRef<Path>>(package_root: T, localdir: &str) -> PathBuf {
     // Check if it's an absolute path
@@ -56,13 +80,96 @@ pub fn normalize_path<T: AsRef<Path>>(path: T) -> PathBuf {
 ///};
 /// ```
 ///
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct AdlPackageDefinition {
-    #[allow(dead_code)]
     pub name: String,
+    #[serde(default)]
     pub dependencies: Vec<AdlPackageRef>,
 }
 
+/// Read the package definition from the `adl-package.json` in a package root
+pub fn read_package_definition<T: AsRef<Path>>(package_root: T) -> Option<AdlPackageDefinition> {
+    let package_json =
+        std::fs::read_to_string(package_root.as_ref().join("adl-package.json")).ok()?;
+    serde_json::from_str(&package_json).ok()
+}
+
+/// The path to `to` relative to the directory `from`, e.g. `../common` from `<root>/app` to `<root>/common`
+pub fn relative_path<T: AsRef<Path>, U: AsRef<Path>>(from: T, to: U) -> PathBuf {
+    let from: Vec<_> = from.as_ref().components().collect();
+    let to: Vec<_> = to.as_ref().components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut path = PathBuf::new();
+    for _ in common..from.len() {
+        path.push("..");
+    }
+    for component in &to[common..] {
+        path.push(component);
+    }
+    if path.as_os_str().is_empty() {
+        path.push(".");
+    }
+    path
+}
+
+/// Where to insert a `localdir` dependency into the text of an `adl-package.json`, as a byte offset
+/// and the text to insert there, keeping the rest of the file as it was written
+pub fn dependency_insertion(package_json: &str, localdir: &str) -> Option<(usize, String)> {
+    let dependency = format!(
+        "{{\"localdir\": {}}}",
+        serde_json::to_string(localdir).ok()?
+    );
+
+    let Some(key) = package_json.find("\"dependencies\"") else {
+        // add the dependencies after the opening brace of the definition
+        let open = package_json.find('{')?;
+        return Some((open + 1, format!("\n  \"dependencies\": [{}],", dependency)));
+    };
+    let open = key + package_json[key..].find('[')?;
+
+    // find the closing bracket of the array, skipping over any brackets in strings
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut close = None;
+    for (i, c) in package_json[open..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            '[' | '{' if !in_string => depth += 1,
+            ']' | '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(open + i);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+
+    let elements = &package_json[open + 1..close];
+    let last_element = elements.trim_end();
+    if last_element.trim_start().is_empty() {
+        return Some((open + 1, dependency));
+    }
+    // line up with the existing dependencies when they are written one per line
+    let separator = match elements.find(|c: char| !c.is_whitespace()) {
+        Some(start) if elements[..start].contains('\n') => {
+            let indent = elements[..start].rsplit('\n').next().unwrap_or_default();
+            format!(",\n{}", indent)
+        }
+        _ => String::from(", "),
+    };
+    Some((
+        open + 1 + last_element.len(),
+        format!("{}{}", separator, dependency),
+    ))
+}
+
 pub fn resolve_import(
     search_dirs: &HashMap<PathBuf, HashSet<Url>>,
     // TODO(med): don't need these parameters if we trust fully in the search dirs being passed in
@@ -246,4 +353,65 @@ mod tests {
             Some(Url::parse("file:///project/adl-strings/common/strings.adl").unwrap())
         );
     }
+
+    #[test]
+    fn test_module_name_for_path() {
+        assert_eq!(
+            module_name_for_path("/project/adl", "/project/adl/common/http.adl"),
+            Some(String::from("common.http"))
+        );
+        assert_eq!(
+            module_name_for_path("/project/adl", "/project/adl/main.adl-java"),
+            Some(String::from("main"))
+        );
+        assert_eq!(
+            module_name_for_path("/project/adl", "/elsewhere/common/http.adl"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_relative_path() {
+        assert_eq!(
+            relative_path("/project/app", "/project/common"),
+            PathBuf::from("../common")
+        );
+        assert_eq!(
+            relative_path("/project", "/project/vendor/http"),
+            PathBuf::from("vendor/http")
+        );
+        assert_eq!(relative_path("/project", "/project"), PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_dependency_insertion() {
+        let insert = |package_json: &str| {
+            let (offset, text) = dependency_insertion(package_json, "../common").unwrap();
+            format!(
+                "{}{}{}",
+                &package_json[..offset],
+                text,
+                &package_json[offset..]
+            )
+        };
+
+        assert_eq!(
+            insert(r#"{"name": "app", "dependencies": []}"#),
+            r#"{"name": "app", "dependencies": [{"localdir": "../common"}]}"#
+        );
+        assert_eq!(
+            insert(r#"{"name": "app", "dependencies": [{"localdir": "../[strings]"}]}"#),
+            r#"{"name": "app", "dependencies": [{"localdir": "../[strings]"}, {"localdir": "../common"}]}"#
+        );
+        assert_eq!(
+            insert(
+                "{\n  \"name\": \"app\",\n  \"dependencies\": [\n    {\"localdir\": \"../strings\"}\n  ]\n}\n"
+            ),
+            "{\n  \"name\": \"app\",\n  \"dependencies\": [\n    {\"localdir\": \"../strings\"},\n    {\"localdir\": \"../common\"}\n  ]\n}\n"
+        );
+        assert_eq!(
+            insert("{\n  \"name\": \"app\"\n}\n"),
+            "{\n  \"dependencies\": [{\"localdir\": \"../common\"}],\n  \"name\": \"app\"\n}\n"
+        );
+    }
 }
@@ -0,0 +1,388 @@
+use std::collections::HashMap;
+
+use async_lsp::{ErrorCode, ResponseError};
+use lsp_types::{
+    Position, PrepareRenameResponse, Range, RenameParams, TextDocumentPositionParams, TextEdit,
+    Url, WorkspaceEdit,
+};
+use tracing::debug;
+use tree_sitter::Node;
+
+use crate::node::{AdlImportDeclaration, NodeKind};
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+use crate::server::Server;
+use crate::server::imports::Fqn;
+use crate::server::typecheck::JsonTypeChecker;
+
+/// Check that a name matches the grammar's `identifier` rule i.e. `[a-zA-Z][a-zA-Z0-9_]*`
+pub fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl Server {
+    pub fn handle_prepare_rename(
+        &mut self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>, ResponseError> {
+        let uri = params.text_document.uri;
+
+        let Some((tree, content)) = self.get_or_parse_document_with_content(&uri) else {
+            return Ok(None);
+        };
+
+        if let Some((fqn, range)) =
+            self.type_at_position(&uri, &tree, content.as_bytes(), &params.position)
+        {
+            return Ok(Some(PrepareRenameResponse::RangeWithPlaceholder {
+                range,
+                placeholder: fqn.type_name().to_string(),
+            }));
+        }
+
+        Ok(self
+            .field_at_position(&uri, &tree, content.as_bytes(), &params.position)
+            .map(
+                |(_, field_name, range)| PrepareRenameResponse::RangeWithPlaceholder {
+                    range,
+                    placeholder: field_name,
+                },
+            ))
+    }
+
+    pub fn handle_rename(
+        &mut self,
+        params: RenameParams,
+    ) -> Result<Option<WorkspaceEdit>, ResponseError> {
+        let uri = params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let new_name = params.new_name;
+
+        if !is_valid_identifier(&new_name) {
+            return Err(ResponseError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("`{}` is not a valid ADL identifier", new_name),
+            ));
+        }
+
+        let Some((tree, content)) = self.get_or_parse_document_with_content(&uri) else {
+            return Ok(None);
+        };
+
+        let Some((fqn, _)) = self.type_at_position(&uri, &tree, content.as_bytes(), &position)
+        else {
+            return match self.field_at_position(&uri, &tree, content.as_bytes(), &position) {
+                Some((fqn, field_name, _)) => self.rename_field(&fqn, &field_name, &new_name),
+                None => Ok(None),
+            };
+        };
+
+        if fqn.type_name() == new_name {
+            return Ok(None);
+        }
+
+        let renamed_fqn = Fqn::from_module_name_and_type_name(fqn.module_name(), &new_name);
+        if self.state.get_import_target(&renamed_fqn).is_some() {
+            return Err(ResponseError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!(
+                    "module {} already defines a type named {}",
+                    fqn.module_name(),
+                    new_name
+                ),
+            ));
+        }
+
+        // modules that import the type refer to it by its unqualified name, which must stay unique there too
+        for importing_uri in self.state.get_files_importing_type(&fqn) {
+            if let Some(module_name) = self.module_with_name_in_scope(&importing_uri, &new_name) {
+                return Err(ResponseError::new(
+                    ErrorCode::INVALID_PARAMS,
+                    format!(
+                        "module {} imports {} but already has a type named {} in scope",
+                        module_name,
+                        fqn.type_name(),
+                        new_name
+                    ),
+                ));
+            }
+        }
+
+        debug!("renaming {:?} to {}", fqn, new_name);
+
+        let mut changes = HashMap::new();
+        for document_uri in self.state.get_document_uris() {
+            let Some((document_tree, document_content)) =
+                self.state.get_document_tree_and_content(&document_uri)
+            else {
+                continue;
+            };
+            let edits = document_tree.type_rename_edits(
+                fqn.module_name(),
+                fqn.type_name(),
+                &new_name,
+                document_content.as_bytes(),
+            );
+            if !edits.is_empty() {
+                changes.insert(document_uri, edits);
+            }
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    fn rename_field(
+        &mut self,
+        fqn: &Fqn,
+        field_name: &str,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, ResponseError> {
+        if field_name == new_name {
+            return Ok(None);
+        }
+
+        let field_names = self
+            .state
+            .get_import_target(fqn)
+            .and_then(|target_uri| self.state.get_document_tree_and_content(&target_uri))
+            .map(|(tree, content)| {
+                tree.find_field_names(fqn.type_name(), content.as_bytes())
+                    .into_iter()
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        if field_names.iter().any(|name| name == new_name) {
+            return Err(ResponseError::new(
+                ErrorCode::INVALID_PARAMS,
+                format!("{} already has a field named {}", fqn.type_name(), new_name),
+            ));
+        }
+
+        debug!("renaming field {} of {:?} to {}", field_name, fqn, new_name);
+
+        let mut changes = HashMap::new();
+        for document_uri in self.state.get_document_uris() {
+            let Some((document_tree, document_content)) =
+                self.state.get_document_tree_and_content(&document_uri)
+            else {
+                continue;
+            };
+            let mut edits = document_tree.field_rename_edits(
+                fqn.module_name(),
+                fqn.type_name(),
+                field_name,
+                new_name,
+                document_content.as_bytes(),
+            );
+            edits.extend(self.json_key_rename_edits(
+                fqn,
+                field_name,
+                new_name,
+                &document_uri,
+                &document_tree,
+                document_content.as_bytes(),
+            ));
+            if !edits.is_empty() {
+                changes.insert(document_uri, edits);
+            }
+        }
+
+        Ok(Some(WorkspaceEdit {
+            changes: Some(changes),
+            ..WorkspaceEdit::default()
+        }))
+    }
+
+    /// Find the edits that rename the keys of JSON values in a document naming the field of a type,
+    /// whether a value has the type itself or nests it within fields, type arguments, aliases or newtypes
+    fn json_key_rename_edits(
+        &self,
+        fqn: &Fqn,
+        field_name: &str,
+        new_name: &str,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+    ) -> Vec<TextEdit> {
+        let Some(target_uri) = self.state.get_import_target(fqn) else {
+            return vec![];
+        };
+        let field = (&target_uri, fqn.type_name(), field_name);
+        let mut get_document = |uri: &Url| self.state.get_document_tree_and_content(uri);
+        let mut checker = JsonTypeChecker::new(self.state.imports(), &mut get_document);
+
+        let mut keys = vec![];
+        for n in
+            tree.find_all_nodes(|n| NodeKind::is_field(n) || NodeKind::is_newtype_definition(n))
+        {
+            if let Some((type_expression, json_value)) = ParsedTree::type_expression_and_default(&n)
+            {
+                let json_type =
+                    checker.resolve_type_expression(uri, tree, content, &type_expression);
+                keys.extend(checker.find_field_keys(&json_type, &json_value, content, field));
+            }
+        }
+        for annotation in tree.find_all_nodes(|n| {
+            NodeKind::is_annotation_decorator(n) || NodeKind::is_annotation_declaration(n)
+        }) {
+            if let Some((annotation_type, json_value)) =
+                ParsedTree::annotation_type_and_value(&annotation)
+            {
+                let json_type =
+                    checker.resolve_annotation_type(uri, tree, content, &annotation_type);
+                keys.extend(checker.find_field_keys(&json_type, &json_value, content, field));
+            }
+        }
+
+        keys.into_iter()
+            .map(|range| TextEdit {
+                range,
+                new_text: format!("\"{}\"", new_name),
+            })
+            .collect()
+    }
+
+    /// The module name of a document that defines or imports a type with the given name
+    fn module_with_name_in_scope(&self, uri: &Url, type_name: &str) -> Option<String> {
+        let (tree, content) = self.state.get_document_tree_and_content(uri)?;
+        let content = content.as_bytes();
+        let module_name = tree.find_module_name(content)?;
+        let is_in_scope = tree.find_local_type_names(content).contains(&type_name)
+            || self
+                .state
+                .get_imported_types(uri)
+                .iter()
+                .any(|fqn| fqn.type_name() == type_name);
+        is_in_scope.then(|| module_name.to_string())
+    }
+
+    /// Resolve the type definition referenced (or defined) by the identifier at a position
+    fn type_at_position(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        position: &Position,
+    ) -> Option<(Fqn, Range)> {
+        let (identifier, node) = tree.get_identifier_at(position, content)?;
+        let parent = node.parent()?;
+        let range = ts_lsp_interop::ts_to_lsp_range(&node.range());
+
+        let fqn = if NodeKind::is_type_name(&parent) {
+            let module_name = tree.find_module_name(content)?;
+            parent
+                .parent()
+                .filter(NodeKind::is_local_definition)
+                .map(|_| Fqn::from_module_name_and_type_name(module_name, identifier))?
+        } else if NodeKind::is_scoped_name(&parent) {
+            // only the final identifier of a scoped name refers to a type
+            let last = parent.named_child(parent.named_child_count().checked_sub(1)?)?;
+            if last.id() != node.id() {
+                return None;
+            }
+            self.resolve_type_reference(uri, tree, &parent, content)?
+        } else {
+            return None;
+        };
+
+        // the definition must be known to the workspace for every reference to be found
+        self.state.get_import_target(&fqn).map(|_| (fqn, range))
+    }
+
+    /// Resolve the struct or union field defined (or referenced by an annotation) by the identifier at a position
+    fn field_at_position(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        position: &Position,
+    ) -> Option<(Fqn, String, Range)> {
+        let (identifier, node) = tree.get_identifier_at(position, content)?;
+        let parent = node.parent()?;
+        let range = ts_lsp_interop::ts_to_lsp_range(&node.range());
+
+        let fqn = if NodeKind::is_field(&parent) {
+            let definition = ParsedTree::find_enclosing_definition(&parent)?;
+            let module_name = tree.find_module_name(content)?;
+            Fqn::from_module_name_and_type_name(
+                module_name,
+                ParsedTree::definition_name(&definition, content)?,
+            )
+        } else if NodeKind::is_field_reference(&parent) {
+            // e.g. `annotation Type::field ...` where the annotated type is the first child
+            let declaration = parent.parent()?;
+            let target_type = declaration.named_child(0)?;
+            self.resolve_type_reference(uri, tree, &target_type, content)?
+        } else {
+            return None;
+        };
+
+        // the field must exist on the definition
+        let target_uri = self.state.get_import_target(&fqn)?;
+        let (target_tree, target_content) = self.get_or_parse_document_with_content(&target_uri)?;
+        target_tree
+            .find_field_names(fqn.type_name(), target_content.as_bytes())
+            .contains(&identifier)
+            .then(|| (fqn, identifier.to_string(), range))
+    }
+
+    /// Resolve the type that a scoped name refers to, whether it is qualified, local or imported
+    fn resolve_type_reference(
+        &self,
+        uri: &Url,
+        tree: &ParsedTree,
+        scoped_name: &Node<'_>,
+        content: &[u8],
+    ) -> Option<Fqn> {
+        if !NodeKind::is_scoped_name(scoped_name)
+            || scoped_name
+                .parent()
+                .is_some_and(|p| NodeKind::is_module_definition(&p))
+        {
+            return None;
+        }
+
+        let identifiers: Vec<&str> = scoped_name
+            .children(&mut scoped_name.walk())
+            .filter(NodeKind::is_identifier)
+            .filter_map(|n| n.utf8_text(content).ok())
+            .collect();
+        let (identifier, qualifiers) = identifiers.split_last()?;
+        let module_name = tree.find_module_name(content)?;
+
+        if !qualifiers.is_empty() {
+            Some(Fqn::from_module_name_and_type_name(
+                &qualifiers.join("."),
+                identifier,
+            ))
+        } else if ParsedTree::is_type_parameter_reference(scoped_name, identifier, content) {
+            None
+        } else if tree.find_local_type_names(content).contains(identifier) {
+            Some(Fqn::from_module_name_and_type_name(module_name, identifier))
+        } else {
+            self.state
+                .get_imported_types(uri)
+                .into_iter()
+                .find(|fqn| fqn.type_name() == *identifier)
+                .or_else(|| {
+                    tree.find_all_nodes(NodeKind::is_import_declaration)
+                        .into_iter()
+                        .filter_map(AdlImportDeclaration::try_new)
+                        .find(|import| import.imported_type_name(content) == Some(identifier))
+                        .map(|import| {
+                            Fqn::from_module_name_and_type_name(
+                                import.module_name(content),
+                                identifier,
+                            )
+                        })
+                })
+        }
+    }
+}
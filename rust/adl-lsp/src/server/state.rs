@@ -1,15 +1,18 @@
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 
 use async_lsp::{ClientSocket, LanguageClient};
-use lsp_types::{DocumentSymbol, PublishDiagnosticsParams, Url};
+use lsp_types::{Diagnostic, DocumentSymbol, PublishDiagnosticsParams, Url};
 use tracing::debug;
 
+use crate::parser::lints::LintConfig;
+use crate::parser::reserved::Target;
 use crate::parser::symbols::DocumentSymbols;
 use crate::parser::{AdlParser, ParsedTree};
+use crate::server::diagnostics::is_adl_file;
 use crate::server::imports::{Fqn, ImportManager, ImportsCache};
-use crate::server::packages;
+use crate::server::packages::{self, AdlPackageDefinition};
 
 /// ADL Language Server state that manages documents and their parsed trees.
 /// Provides atomic operations to ensure document content and tree are updated together.
@@ -17,11 +20,13 @@ use crate::server::packages;
 pub struct AdlLanguageServerState {
     adl_file_to_package_root: Arc<RwLock<HashMap<Url, PathBuf>>>,
     package_root_to_adl_files: Arc<RwLock<HashMap<PathBuf, HashSet<Url>>>>,
+    package_definitions: Arc<RwLock<HashMap<PathBuf, Option<AdlPackageDefinition>>>>,
 
     documents: Arc<RwLock<HashMap<Url, String>>>,
     trees: Arc<RwLock<HashMap<Url, ParsedTree>>>,
 
     symbols: Arc<RwLock<HashMap<Url, Vec<DocumentSymbol>>>>,
+    diagnostics: Arc<RwLock<HashMap<Url, Vec<Diagnostic>>>>,
     import_manager: ImportsCache,
 }
 
@@ -36,6 +41,8 @@ impl AdlLanguageServerState {
         &self,
         client: &mut ClientSocket,
         parser: &mut AdlParser,
+        lints: &LintConfig,
+        targets: &[Target],
         uri: &Url,
         contents: String,
     ) -> Option<()> {
@@ -44,7 +51,9 @@ impl AdlLanguageServerState {
         let parsed_tree = parser.parse(uri.clone(), &contents)?;
 
         debug!("collecting diagnostics on parse tree for {}", uri.path());
-        let diagnostics = parsed_tree.collect_diagnostics(&contents);
+        let mut diagnostics = parsed_tree.collect_diagnostics(&contents);
+        diagnostics.extend(parsed_tree.collect_naming_diagnostics(contents.as_bytes()));
+        diagnostics.extend(parsed_tree.collect_missing_doc_diagnostics(contents.as_bytes()));
 
         let symbols = parsed_tree.collect_document_symbols(contents.as_bytes());
         let mut symbols_cache = self.symbols.write().expect("poisoned");
@@ -57,55 +66,108 @@ impl AdlLanguageServerState {
         let mut documents = self.documents.write().expect("poisoned");
         let mut trees = self.trees.write().expect("poisoned");
 
-        // TODO(med): also find the package root by walking up the file system from the module definition
-        let package_root = packages::find_package_root_by_marker(uri.path());
-        if let Some(package_root) = package_root {
-            adl_file_to_package_root.insert(uri.clone(), package_root.clone());
-            package_root_to_adl_files
-                .entry(package_root)
-                .or_default()
-                .insert(uri.clone());
-        }
+        Self::register_package_file(
+            uri,
+            &mut adl_file_to_package_root,
+            &mut package_root_to_adl_files,
+        );
 
         // pass closure allowing import_manager to recursively `resolve_and_register_imports`
         // alternative may be to use a queue here and have each call of resolve_and_register_imports
         // chain further files to parse
-        let mut get_or_parse_document_tree = |target_uri: &Url| -> Option<ParsedTree> {
-            if let Some(existing_tree) = trees.get(target_uri) {
-                return Some(existing_tree.clone());
-            }
-
-            // If not found, try to parse the file
-            if let Ok(target_content) = std::fs::read_to_string(target_uri.path()) {
-                debug!(
-                    "parsing target document for import resolution: {}",
-                    target_uri
-                );
-                if let Some(parsed_tree) =
-                    parser.parse(target_uri.clone(), target_content.as_bytes())
-                {
-                    // Store it for future use
-                    trees.insert(target_uri.clone(), parsed_tree.clone());
-                    documents.insert(target_uri.clone(), target_content);
-                    return Some(parsed_tree);
-                }
-            }
-
-            None
+        let mut get_or_parse_document = |target_uri: &Url| -> Option<(ParsedTree, String)> {
+            Self::get_or_parse_document(parser, &mut trees, &mut documents, target_uri)
         };
 
-        self.import_manager.resolve_and_register_imports(
+        diagnostics.extend(self.import_manager.resolve_and_register_imports(
             &package_root_to_adl_files,
             uri,
             &parsed_tree,
             contents.as_bytes(),
-            &mut get_or_parse_document_tree,
-        );
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_type_reference_diagnostics(
+            &package_root_to_adl_files,
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_type_arity_diagnostics(
+            &package_root_to_adl_files,
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_json_value_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_alias_cycle_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_unconstructible_type_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(Self::collect_reserved_word_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            targets,
+            &mut get_or_parse_document,
+        ));
+        diagnostics.extend(self.collect_unused_star_import_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+        ));
+        diagnostics.extend(self.collect_ambiguous_import_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+        ));
+        diagnostics.extend(Self::collect_module_path_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            adl_file_to_package_root.get(uri),
+        ));
+        diagnostics.extend(self.collect_package_dependency_diagnostics(
+            &package_root_to_adl_files,
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            adl_file_to_package_root.get(uri),
+        ));
+        diagnostics.extend(Self::collect_duplicate_module_diagnostics(
+            uri,
+            &parsed_tree,
+            contents.as_bytes(),
+            &documents,
+            &trees,
+        ));
+
+        let diagnostics = lints.apply(diagnostics, &contents);
 
         // Store document contents
         documents.insert(uri.clone(), contents);
         trees.insert(uri.clone(), parsed_tree.clone());
 
+        // keep the published diagnostics so that pulled diagnostics agree with them
+        self.diagnostics
+            .write()
+            .expect("poisoned")
+            .insert(uri.clone(), diagnostics.clone());
+
         // TODO(alex): the state layer is probably the wrong layer to be publishing diagnostics or accessing the client handle
         let _res = client.publish_diagnostics(PublishDiagnosticsParams {
             uri: uri.clone(),
@@ -116,6 +178,98 @@ impl AdlLanguageServerState {
         Some(())
     }
 
+    /// Get a stored document, parsing it from disk and storing it if it has not been seen yet
+    fn get_or_parse_document(
+        parser: &mut AdlParser,
+        trees: &mut HashMap<Url, ParsedTree>,
+        documents: &mut HashMap<Url, String>,
+        target_uri: &Url,
+    ) -> Option<(ParsedTree, String)> {
+        if let (Some(existing_tree), Some(existing_content)) =
+            (trees.get(target_uri), documents.get(target_uri))
+        {
+            return Some((existing_tree.clone(), existing_content.clone()));
+        }
+
+        // If not found, try to parse the file
+        if let Ok(target_content) = std::fs::read_to_string(target_uri.path()) {
+            debug!(
+                "parsing target document for import resolution: {}",
+                target_uri
+            );
+            if let Some(parsed_tree) = parser.parse(target_uri.clone(), target_content.as_bytes()) {
+                // Store it for future use
+                trees.insert(target_uri.clone(), parsed_tree.clone());
+                documents.insert(target_uri.clone(), target_content.clone());
+                return Some((parsed_tree, target_content));
+            }
+        }
+
+        None
+    }
+
+    /// Resolve the imports of documents ahead of ingesting them, so that a document's diagnostics can
+    /// follow types through the imports of the documents it depends on whatever order they are ingested in
+    pub fn register_imports(&self, parser: &mut AdlParser, contents: &[(Url, String)]) {
+        let package_root_to_adl_files = self.package_root_to_adl_files.read().expect("poisoned");
+        let mut documents = self.documents.write().expect("poisoned");
+        let mut trees = self.trees.write().expect("poisoned");
+
+        for (uri, content) in contents {
+            if let Some(parsed_tree) = parser.parse(uri.clone(), content.as_bytes()) {
+                trees.insert(uri.clone(), parsed_tree);
+                documents.insert(uri.clone(), content.clone());
+            }
+        }
+
+        for (uri, content) in contents {
+            let Some(parsed_tree) = trees.get(uri).cloned() else {
+                continue;
+            };
+            // unresolved imports are reported once the document is ingested
+            let _diagnostics = self.import_manager.resolve_and_register_imports(
+                &package_root_to_adl_files,
+                uri,
+                &parsed_tree,
+                content.as_bytes(),
+                &mut |target_uri: &Url| {
+                    Self::get_or_parse_document(parser, &mut trees, &mut documents, target_uri)
+                },
+            );
+        }
+    }
+
+    /// Register files under their package roots ahead of ingesting them, so that imports between
+    /// packages resolve whatever order the files are ingested in
+    pub fn register_package_files<'a>(&self, uris: impl IntoIterator<Item = &'a Url>) {
+        let mut adl_file_to_package_root = self.adl_file_to_package_root.write().expect("poisoned");
+        let mut package_root_to_adl_files =
+            self.package_root_to_adl_files.write().expect("poisoned");
+        for uri in uris {
+            Self::register_package_file(
+                uri,
+                &mut adl_file_to_package_root,
+                &mut package_root_to_adl_files,
+            );
+        }
+    }
+
+    fn register_package_file(
+        uri: &Url,
+        adl_file_to_package_root: &mut HashMap<Url, PathBuf>,
+        package_root_to_adl_files: &mut HashMap<PathBuf, HashSet<Url>>,
+    ) {
+        // TODO(med): also find the package root by walking up the file system from the module definition
+        let package_root = packages::find_package_root_by_marker(uri.path());
+        if let Some(package_root) = package_root {
+            adl_file_to_package_root.insert(uri.clone(), package_root.clone());
+            package_root_to_adl_files
+                .entry(package_root)
+                .or_default()
+                .insert(uri.clone());
+        }
+    }
+
     pub fn clear_cache(&mut self) {
         self.adl_file_to_package_root
             .write()
@@ -125,22 +279,159 @@ impl AdlLanguageServerState {
             .write()
             .expect("poisoned")
             .clear();
+        self.package_definitions.write().expect("poisoned").clear();
         self.documents.write().expect("poisoned").clear();
         self.trees.write().expect("poisoned").clear();
         self.symbols.write().expect("poisoned").clear();
+        self.diagnostics.write().expect("poisoned").clear();
         self.import_manager.clear_cache();
     }
 
+    /// Forget a document that has been moved or deleted, returning the documents that imported from it
+    pub fn remove_document(&self, uri: &Url) -> Vec<Url> {
+        debug!("removing document: {uri:?}");
+
+        let dependents = self
+            .import_manager
+            .cache()
+            .lookup_files_that_import_from(uri);
+        self.import_manager.cache().remove_source(uri);
+
+        if let Some(package_root) = self
+            .adl_file_to_package_root
+            .write()
+            .expect("poisoned")
+            .remove(uri)
+        {
+            let mut package_root_to_adl_files =
+                self.package_root_to_adl_files.write().expect("poisoned");
+            if let Some(adl_files) = package_root_to_adl_files.get_mut(&package_root) {
+                adl_files.remove(uri);
+            }
+        }
+
+        self.documents.write().expect("poisoned").remove(uri);
+        self.trees.write().expect("poisoned").remove(uri);
+        self.symbols.write().expect("poisoned").remove(uri);
+        self.diagnostics.write().expect("poisoned").remove(uri);
+
+        dependents
+    }
+
+    /// Get the documents that import from a module or use its fully qualified names, whether or not they resolved
+    pub fn get_documents_referencing_module(&self, module_name: &str) -> Vec<Url> {
+        let documents = self.documents.read().expect("poisoned");
+        let trees = self.trees.read().expect("poisoned");
+
+        trees
+            .iter()
+            .filter(|(uri, tree)| {
+                documents
+                    .get(*uri)
+                    .is_some_and(|content| tree.references_module(module_name, content.as_bytes()))
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    /// Get the ADL files (i.e. not companion annotation files) that declare a module
+    pub fn get_documents_defining_module(&self, module_name: &str) -> Vec<Url> {
+        let documents = self.documents.read().expect("poisoned");
+        let trees = self.trees.read().expect("poisoned");
+
+        trees
+            .iter()
+            .filter(|(uri, tree)| {
+                is_adl_file(uri)
+                    && documents.get(*uri).is_some_and(|content| {
+                        tree.find_module_name(content.as_bytes()) == Some(module_name)
+                    })
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect()
+    }
+
+    /// Get the package root that a document was registered under
+    pub fn get_package_root(&self, uri: &Url) -> Option<PathBuf> {
+        self.adl_file_to_package_root
+            .read()
+            .expect("poisoned")
+            .get(uri)
+            .cloned()
+    }
+
+    /// Get the definition in the `adl-package.json` of a package root, reading it only the first time
+    pub fn get_package_definition(&self, package_root: &Path) -> Option<AdlPackageDefinition> {
+        let package_root = packages::normalize_path(package_root);
+        if let Some(package_definition) = self
+            .package_definitions
+            .read()
+            .expect("poisoned")
+            .get(&package_root)
+        {
+            return package_definition.clone();
+        }
+
+        let package_definition = packages::read_package_definition(&package_root);
+        self.package_definitions
+            .write()
+            .expect("poisoned")
+            .insert(package_root, package_definition.clone());
+        package_definition
+    }
+
+    /// Get the files registered under each package root, in a stable order
+    pub fn get_package_adl_files(&self) -> Vec<(PathBuf, Vec<Url>)> {
+        let mut package_adl_files: Vec<(PathBuf, Vec<Url>)> = self
+            .package_root_to_adl_files
+            .read()
+            .expect("poisoned")
+            .iter()
+            .map(|(package_root, adl_files)| {
+                let mut adl_files: Vec<Url> = adl_files.iter().cloned().collect();
+                adl_files.sort();
+                (package_root.clone(), adl_files)
+            })
+            .collect();
+        package_adl_files.sort();
+        package_adl_files
+    }
+
     /// Get the target URI for an identifier from the imports table
     pub fn get_import_target(&self, fqn: &Fqn) -> Option<Url> {
         self.import_manager.cache().lookup_fqn(fqn)
     }
 
+    /// Get the table of resolved imports and definitions
+    pub fn imports(&self) -> &ImportsCache {
+        self.import_manager.cache()
+    }
+
     /// Get all files that import a specific type
     pub fn get_files_importing_type(&self, fqn: &Fqn) -> Vec<Url> {
         self.import_manager.cache().lookup_files_that_import(fqn)
     }
 
+    /// Get all types defined in the workspace and the files that define them
+    pub fn get_all_definitions(&self) -> Vec<(Fqn, Url)> {
+        self.import_manager.cache().lookup_all_definitions()
+    }
+
+    /// Get all types imported by a file
+    pub fn get_imported_types(&self, uri: &Url) -> Vec<Fqn> {
+        self.import_manager.cache().lookup_imported_symbols(uri)
+    }
+
+    /// Get the URIs of all documents that have been parsed
+    pub fn get_document_uris(&self) -> Vec<Url> {
+        self.trees
+            .read()
+            .expect("poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
     /// Get the content of a document if it exists
     pub fn get_document_content(&self, uri: &Url) -> Option<String> {
         self.documents.read().expect("poisoned").get(uri).cloned()
@@ -162,6 +453,11 @@ impl AdlLanguageServerState {
         }
     }
 
+    /// Get the diagnostics last published for a document
+    pub fn get_cached_diagnostics(&self, uri: &Url) -> Option<Vec<Diagnostic>> {
+        self.diagnostics.read().expect("poisoned").get(uri).cloned()
+    }
+
     /// Get cached document symbols if available
     pub fn get_cached_document_symbols(&self, uri: &Url) -> Option<Vec<DocumentSymbol>> {
         self.symbols.read().expect("poisoned").get(uri).cloned()
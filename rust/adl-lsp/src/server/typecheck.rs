@@ -0,0 +1,969 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use async_lsp::lsp_types::{Range, Url};
+use tree_sitter::Node;
+
+use crate::node::{AdlPrimitive, NodeKind};
+use crate::parser::ParsedTree;
+use crate::parser::tree::Tree;
+use crate::parser::ts_lsp_interop;
+use crate::server::imports::{Fqn, ImportsCache};
+
+/// Aliases and newtypes are followed at most this many times, which guards against cyclic aliases
+const MAX_EXPANSION_DEPTH: usize = 64;
+
+/// The module defining the standard annotations, which are in scope without an import
+const SYS_ANNOTATIONS: &str = "sys.annotations";
+
+/// The type that a JSON value must conform to, with any type parameters substituted
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonType {
+    /// Any JSON value e.g. for `Json`, a type parameter without an argument or a type that could not be resolved
+    Any,
+    Primitive(AdlPrimitive, Vec<JsonType>),
+    /// A type defined in a document, along with its type arguments
+    Defined {
+        uri: Url,
+        type_name: String,
+        type_arguments: Vec<JsonType>,
+    },
+}
+
+/// A JSON value that does not conform to its type
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonTypeError {
+    pub range: Range,
+    pub message: String,
+}
+
+/// A document that type names are resolved in
+struct Document {
+    uri: Url,
+    tree: ParsedTree,
+    content: String,
+}
+
+/// The JSON representation of a user defined type
+enum Definition {
+    /// A type alias or a newtype, which are serialized as the type they wrap
+    Alias {
+        json_type: JsonType,
+        has_default: bool,
+    },
+    Struct(Vec<JsonField>),
+    Union(Vec<JsonField>),
+}
+
+struct JsonField {
+    name: String,
+    /// The field name, unless it is overridden with `SerializedName`
+    json_key: String,
+    json_type: JsonType,
+    has_default: bool,
+}
+
+/// Resolves ADL types across the workspace and checks JSON values against them
+pub struct JsonTypeChecker<'a, F>
+where
+    F: FnMut(&Url) -> Option<(ParsedTree, String)>,
+{
+    imports: &'a ImportsCache,
+    get_or_parse_document: &'a mut F,
+    documents: HashMap<Url, Option<Rc<Document>>>,
+    /// Types known to have a finite value
+    constructible_types: HashSet<JsonType>,
+}
+
+impl<'a, F> JsonTypeChecker<'a, F>
+where
+    F: FnMut(&Url) -> Option<(ParsedTree, String)>,
+{
+    pub fn new(imports: &'a ImportsCache, get_or_parse_document: &'a mut F) -> Self {
+        Self {
+            imports,
+            get_or_parse_document,
+            documents: HashMap::new(),
+            constructible_types: HashSet::new(),
+        }
+    }
+
+    /// Resolve a type expression appearing in a document, which takes precedence over any stored
+    /// version of the same document
+    pub fn resolve_type_expression(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_expression: &Node,
+    ) -> JsonType {
+        let document = self.insert_document(uri, tree, content);
+        self.resolve(&document, type_expression, &HashMap::new())
+    }
+
+    /// Resolve the type of an annotation appearing in a document, falling back to `sys.annotations`
+    /// for unqualified names as the ADL compiler does
+    pub fn resolve_annotation_type(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        scoped_name: &Node,
+    ) -> JsonType {
+        let document = self.insert_document(uri, tree, content);
+        let json_type = self.resolve_named(&document, scoped_name, vec![], &HashMap::new());
+        if !matches!(json_type, JsonType::Any) || scoped_name.named_child_count() != 1 {
+            return json_type;
+        }
+
+        let Ok(type_name) = scoped_name.utf8_text(content) else {
+            return JsonType::Any;
+        };
+        self.imports
+            .lookup_fqn(&Fqn::from_module_name_and_type_name(
+                SYS_ANNOTATIONS,
+                type_name,
+            ))
+            .map_or(JsonType::Any, |uri| JsonType::Defined {
+                uri,
+                type_name: type_name.to_string(),
+                type_arguments: vec![],
+            })
+    }
+
+    /// Check a JSON value from a document against a type, returning every place it does not conform
+    pub fn check(
+        &mut self,
+        json_type: &JsonType,
+        json_value: &Node,
+        content: &[u8],
+    ) -> Vec<JsonTypeError> {
+        let mut errors = vec![];
+        self.check_value(json_type, json_value, content, 0, &mut errors);
+        errors
+    }
+
+    /// Find the keys of a JSON value, and of every value nested within it, that name a field given as
+    /// the document defining a struct or union, the type's name and the field's name
+    pub fn find_field_keys(
+        &mut self,
+        json_type: &JsonType,
+        json_value: &Node,
+        content: &[u8],
+        field: (&Url, &str, &str),
+    ) -> Vec<Range> {
+        let mut keys = vec![];
+        self.collect_field_keys(json_type, json_value, content, field, 0, &mut keys);
+        keys
+    }
+
+    fn collect_field_keys(
+        &mut self,
+        json_type: &JsonType,
+        json_value: &Node,
+        content: &[u8],
+        field: (&Url, &str, &str),
+        depth: usize,
+        keys: &mut Vec<Range>,
+    ) {
+        let Some(json) = json_value.child(0) else {
+            return;
+        };
+
+        match json_type {
+            JsonType::Any => {}
+            JsonType::Primitive(primitive, type_arguments) => {
+                let type_argument = type_arguments.first().cloned().unwrap_or(JsonType::Any);
+                let nested_values: Vec<Node> = match primitive {
+                    AdlPrimitive::Vector if NodeKind::is_json_array(&json) => json
+                        .named_children(&mut json.walk())
+                        .filter(NodeKind::is_json_value)
+                        .collect(),
+                    AdlPrimitive::StringMap if NodeKind::is_json_object(&json) => json
+                        .named_children(&mut json.walk())
+                        .filter_map(|pair| pair.named_child(1))
+                        .collect(),
+                    AdlPrimitive::Nullable if json.kind() != "null" => vec![*json_value],
+                    _ => vec![],
+                };
+                for nested_value in nested_values {
+                    self.collect_field_keys(
+                        &type_argument,
+                        &nested_value,
+                        content,
+                        field,
+                        depth,
+                        keys,
+                    );
+                }
+            }
+            JsonType::Defined {
+                uri,
+                type_name,
+                type_arguments,
+            } => {
+                if depth > MAX_EXPANSION_DEPTH {
+                    return;
+                }
+                let fields = match self.definition(uri, type_name, type_arguments) {
+                    None => return,
+                    Some(Definition::Alias { json_type, .. }) => {
+                        return self.collect_field_keys(
+                            &json_type,
+                            json_value,
+                            content,
+                            field,
+                            depth + 1,
+                            keys,
+                        );
+                    }
+                    Some(Definition::Struct(fields)) | Some(Definition::Union(fields)) => fields,
+                };
+                if !NodeKind::is_json_object(&json) {
+                    return;
+                }
+
+                let (field_uri, field_type_name, field_name) = field;
+                let is_renamed_type = uri == field_uri && type_name == field_type_name;
+                for (key, pair, value) in object_pairs(&json, content) {
+                    let Some(json_field) = fields.iter().find(|f| f.json_key == key) else {
+                        continue;
+                    };
+                    // keys overridden with `SerializedName` don't follow the field name
+                    if is_renamed_type && json_field.name == field_name && key == field_name {
+                        keys.extend(
+                            pair.named_child(0)
+                                .map(|key| ts_lsp_interop::ts_to_lsp_range(&key.range())),
+                        );
+                    }
+                    self.collect_field_keys(&json_field.json_type, &value, content, field, 0, keys);
+                }
+            }
+        }
+    }
+
+    /// Follow the chain of `type` aliases starting at one defined in a document, returning the
+    /// aliases that lead back to it when it is ultimately defined as itself
+    pub fn find_alias_cycle(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_name: &str,
+    ) -> Option<Vec<Fqn>> {
+        let document = self.insert_document(uri, tree, content);
+        let start = (uri.clone(), type_name.to_string());
+        let mut chain = vec![start.clone()];
+        let mut fqns = vec![Fqn::from_module_name_and_type_name(
+            document
+                .tree
+                .find_module_name(document.content.as_bytes())?,
+            type_name,
+        )];
+
+        loop {
+            let (current_uri, current_name) = chain.last()?.clone();
+            let (next_uri, next_name, next_fqn) = self.alias_target(&current_uri, &current_name)?;
+            let next = (next_uri, next_name);
+            if next == start {
+                return Some(fqns);
+            }
+            // a cycle that does not pass through the starting alias is reported by its own members
+            if chain.contains(&next) {
+                return None;
+            }
+            chain.push(next);
+            fqns.push(next_fqn);
+        }
+    }
+
+    /// The user defined type that a `type` alias stands for, ignoring any type arguments
+    fn alias_target(&mut self, uri: &Url, type_name: &str) -> Option<(Url, String, Fqn)> {
+        let document = self.document(uri)?;
+        let content = document.content.as_bytes();
+        let type_expression = document
+            .tree
+            .find_all_nodes(NodeKind::is_type_definition)
+            .into_iter()
+            .find(|n| ParsedTree::definition_name(n, content) == Some(type_name))
+            .and_then(|definition| {
+                definition
+                    .children(&mut definition.walk())
+                    .find(NodeKind::is_type_expression)
+            })?;
+
+        let JsonType::Defined {
+            uri: target_uri,
+            type_name: target_name,
+            ..
+        } = self.resolve(&document, &type_expression, &HashMap::new())
+        else {
+            return None;
+        };
+        let target = self.document(&target_uri)?;
+        let target_module = target.tree.find_module_name(target.content.as_bytes())?;
+        let fqn = Fqn::from_module_name_and_type_name(target_module, &target_name);
+        Some((target_uri, target_name, fqn))
+    }
+
+    /// Whether a finite value of a type defined in a document exists, taking any type parameters to be
+    /// constructible
+    pub fn is_constructible(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_name: &str,
+    ) -> bool {
+        let json_type = self.local_type(uri, tree, content, type_name);
+        self.is_constructible_type(&json_type, &mut vec![])
+    }
+
+    /// Find the fields (and aliases) through which a type defined in a document must always contain
+    /// another value of itself, which makes it impossible to construct
+    pub fn find_self_containment(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_name: &str,
+    ) -> Option<Vec<String>> {
+        let json_type = self.local_type(uri, tree, content, type_name);
+        let mut chain = vec![];
+        self.find_containment_chain(&json_type, &json_type, &mut chain, &mut vec![])
+            .then_some(chain)
+    }
+
+    fn local_type(
+        &mut self,
+        uri: &Url,
+        tree: &ParsedTree,
+        content: &[u8],
+        type_name: &str,
+    ) -> JsonType {
+        let document = self.insert_document(uri, tree, content);
+        let type_parameter_count = document
+            .tree
+            .find_all_nodes(NodeKind::is_local_definition)
+            .into_iter()
+            .find(|n| ParsedTree::definition_name(n, content) == Some(type_name))
+            .map_or(0, |definition| {
+                ParsedTree::type_parameter_names(&definition, content).len()
+            });
+        JsonType::Defined {
+            uri: uri.clone(),
+            type_name: type_name.to_string(),
+            type_arguments: vec![JsonType::Any; type_parameter_count],
+        }
+    }
+
+    fn insert_document(&mut self, uri: &Url, tree: &ParsedTree, content: &[u8]) -> Rc<Document> {
+        let document = Rc::new(Document {
+            uri: uri.clone(),
+            tree: tree.clone(),
+            content: String::from_utf8_lossy(content).to_string(),
+        });
+        self.documents
+            .insert(uri.clone(), Some(Rc::clone(&document)));
+        document
+    }
+
+    fn document(&mut self, uri: &Url) -> Option<Rc<Document>> {
+        if let Some(document) = self.documents.get(uri) {
+            return document.clone();
+        }
+        let document = (self.get_or_parse_document)(uri).map(|(tree, content)| {
+            Rc::new(Document {
+                uri: uri.clone(),
+                tree,
+                content,
+            })
+        });
+        self.documents.insert(uri.clone(), document.clone());
+        document
+    }
+
+    fn resolve(
+        &mut self,
+        document: &Document,
+        type_expression: &Node,
+        bindings: &HashMap<String, JsonType>,
+    ) -> JsonType {
+        let Some(type_node) = type_expression.named_child(0) else {
+            return JsonType::Any;
+        };
+        let type_arguments: Vec<JsonType> = type_expression
+            .named_children(&mut type_expression.walk())
+            .chain(type_node.named_children(&mut type_node.walk()))
+            .filter(NodeKind::is_type_arguments)
+            .flat_map(|type_arguments| {
+                type_arguments
+                    .named_children(&mut type_arguments.walk())
+                    .filter(NodeKind::is_type_expression)
+                    .collect::<Vec<_>>()
+            })
+            .map(|argument| self.resolve(document, &argument, bindings))
+            .collect();
+
+        if NodeKind::is_primitive_type(&type_node) {
+            match type_node
+                .child(0)
+                .and_then(|keyword| AdlPrimitive::from_name(keyword.kind()))
+            {
+                Some(AdlPrimitive::Json) | None => JsonType::Any,
+                Some(primitive) => JsonType::Primitive(primitive, type_arguments),
+            }
+        } else {
+            self.resolve_named(document, &type_node, type_arguments, bindings)
+        }
+    }
+
+    fn resolve_named(
+        &mut self,
+        document: &Document,
+        scoped_name: &Node,
+        type_arguments: Vec<JsonType>,
+        bindings: &HashMap<String, JsonType>,
+    ) -> JsonType {
+        let content = document.content.as_bytes();
+        let identifiers: Vec<&str> = scoped_name
+            .children(&mut scoped_name.walk())
+            .filter(NodeKind::is_identifier)
+            .filter_map(|n| n.utf8_text(content).ok())
+            .collect();
+        let Some((type_name, qualifiers)) = identifiers.split_last() else {
+            return JsonType::Any;
+        };
+
+        if qualifiers.is_empty() {
+            if let Some(bound) = bindings.get(*type_name) {
+                return bound.clone();
+            }
+            if ParsedTree::is_type_parameter_reference(scoped_name, type_name, content) {
+                return JsonType::Any;
+            }
+        }
+
+        // names are resolved as the imports table resolves them, leaving ambiguous names unchecked
+        let Some(module_name) = document.tree.find_module_name(content) else {
+            return JsonType::Any;
+        };
+        let fqn = if qualifiers.is_empty() {
+            match &self.imports.lookup_unqualified_name(
+                &document.uri,
+                &document.tree,
+                content,
+                type_name,
+            )[..]
+            {
+                [fqn] => fqn.clone(),
+                _ => return JsonType::Any,
+            }
+        } else {
+            Fqn::from_module_name_and_type_name(&qualifiers.join("."), type_name)
+        };
+
+        let defining_uri = if fqn.module_name() == module_name {
+            document
+                .tree
+                .find_local_type_names(content)
+                .contains(type_name)
+                .then(|| document.uri.clone())
+        } else {
+            self.imports.lookup_fqn(&fqn)
+        };
+        defining_uri.map_or(JsonType::Any, |uri| JsonType::Defined {
+            uri,
+            type_name: type_name.to_string(),
+            type_arguments,
+        })
+    }
+
+    /// Find how a user defined type is represented in JSON, given its type arguments
+    fn definition(
+        &mut self,
+        uri: &Url,
+        type_name: &str,
+        type_arguments: &[JsonType],
+    ) -> Option<Definition> {
+        let document = self.document(uri)?;
+        let content = document.content.as_bytes();
+        let definition = document
+            .tree
+            .find_all_nodes(NodeKind::is_local_definition)
+            .into_iter()
+            .find(|n| ParsedTree::definition_name(n, content) == Some(type_name))?;
+
+        let bindings: HashMap<String, JsonType> =
+            ParsedTree::type_parameter_names(&definition, content)
+                .into_iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    (
+                        name.to_string(),
+                        type_arguments.get(i).cloned().unwrap_or(JsonType::Any),
+                    )
+                })
+                .collect();
+
+        if NodeKind::is_type_definition(&definition) || NodeKind::is_newtype_definition(&definition)
+        {
+            let mut cursor = definition.walk();
+            let type_expression = definition
+                .children(&mut cursor)
+                .find(NodeKind::is_type_expression)?;
+            let has_default = definition
+                .children(&mut cursor)
+                .any(|n| NodeKind::is_json_value(&n));
+            return Some(Definition::Alias {
+                json_type: self.resolve(&document, &type_expression, &bindings),
+                has_default,
+            });
+        }
+
+        let declared_serialized_names = document.tree.find_declared_serialized_names(content);
+        let field_block = definition
+            .children(&mut definition.walk())
+            .find(NodeKind::is_field_block)?;
+        let fields = field_block
+            .named_children(&mut field_block.walk())
+            .filter(NodeKind::is_field)
+            .filter_map(|field| {
+                let name = ParsedTree::field_name_node(&field)?
+                    .utf8_text(content)
+                    .ok()?;
+                let json_key = ParsedTree::serialized_name(&field, content)
+                    .or_else(|| declared_serialized_names.get(&(type_name, name)).copied())
+                    .and_then(|json_value| ParsedTree::json_string_value(&json_value, content))
+                    .unwrap_or_else(|| name.to_string());
+                let mut cursor = field.walk();
+                let type_expression = field
+                    .children(&mut cursor)
+                    .find(NodeKind::is_type_expression)?;
+                let has_default = field
+                    .children(&mut cursor)
+                    .any(|n| NodeKind::is_json_value(&n));
+                Some(JsonField {
+                    name: name.to_string(),
+                    json_key,
+                    json_type: self.resolve(&document, &type_expression, &bindings),
+                    has_default,
+                })
+            })
+            .collect();
+
+        if NodeKind::is_union_definition(&definition) {
+            Some(Definition::Union(fields))
+        } else {
+            Some(Definition::Struct(fields))
+        }
+    }
+
+    /// Whether a value of the type can be omitted from a struct because the type provides a default
+    fn has_type_default(&mut self, json_type: &JsonType, depth: usize) -> bool {
+        let JsonType::Defined {
+            uri,
+            type_name,
+            type_arguments,
+        } = json_type
+        else {
+            return false;
+        };
+        if depth > MAX_EXPANSION_DEPTH {
+            return false;
+        }
+        match self.definition(uri, type_name, type_arguments) {
+            Some(Definition::Alias {
+                json_type,
+                has_default,
+            }) => has_default || self.has_type_default(&json_type, depth + 1),
+            _ => false,
+        }
+    }
+
+    /// Whether a field must be present in every value of its struct
+    fn is_required(&mut self, field: &JsonField) -> bool {
+        !field.has_default && !self.has_type_default(&field.json_type, 0)
+    }
+
+    /// Whether a finite value of a type exists, assuming that none of the types still being checked
+    /// further up have one
+    fn is_constructible_type(
+        &mut self,
+        json_type: &JsonType,
+        in_progress: &mut Vec<JsonType>,
+    ) -> bool {
+        let JsonType::Defined {
+            uri,
+            type_name,
+            type_arguments,
+        } = json_type
+        else {
+            // every primitive has a value, including `Nullable`, `Vector` and `StringMap` of anything
+            return true;
+        };
+        if self.constructible_types.contains(json_type) {
+            return true;
+        }
+        if in_progress.contains(json_type) {
+            return false;
+        }
+        // types whose type arguments keep growing are given the benefit of the doubt
+        if in_progress.len() > MAX_EXPANSION_DEPTH {
+            return true;
+        }
+        let Some(definition) = self.definition(uri, type_name, type_arguments) else {
+            return true;
+        };
+
+        in_progress.push(json_type.clone());
+        let is_constructible = match definition {
+            Definition::Alias {
+                json_type,
+                has_default,
+            } => has_default || self.is_constructible_type(&json_type, in_progress),
+            Definition::Struct(fields) => fields.iter().all(|field| {
+                !self.is_required(field)
+                    || self.is_constructible_type(&field.json_type, in_progress)
+            }),
+            // an empty union is most likely still being written
+            Definition::Union(fields) => {
+                fields.is_empty()
+                    || fields
+                        .iter()
+                        .any(|field| self.is_constructible_type(&field.json_type, in_progress))
+            }
+        };
+        in_progress.pop();
+
+        if is_constructible {
+            self.constructible_types.insert(json_type.clone());
+        }
+        is_constructible
+    }
+
+    /// Search the unconstructible types that a value of `json_type` must contain for a path back to
+    /// `start`, recording each field or alias followed along the way
+    fn find_containment_chain(
+        &mut self,
+        start: &JsonType,
+        json_type: &JsonType,
+        chain: &mut Vec<String>,
+        visited: &mut Vec<JsonType>,
+    ) -> bool {
+        let JsonType::Defined {
+            uri,
+            type_name,
+            type_arguments,
+        } = json_type
+        else {
+            return false;
+        };
+        if !chain.is_empty() && json_type == start {
+            return true;
+        }
+        if visited.contains(json_type) || visited.len() > MAX_EXPANSION_DEPTH {
+            return false;
+        }
+        let Some(definition) = self.definition(uri, type_name, type_arguments) else {
+            return false;
+        };
+        visited.push(json_type.clone());
+
+        let contained: Vec<(String, JsonType)> = match definition {
+            Definition::Alias {
+                json_type,
+                has_default: false,
+            } => vec![(type_name.clone(), json_type)],
+            Definition::Alias { .. } => vec![],
+            Definition::Struct(fields) => fields
+                .into_iter()
+                .filter(|field| self.is_required(field))
+                .map(|field| (format!("{}.{}", type_name, field.name), field.json_type))
+                .collect(),
+            Definition::Union(fields) => fields
+                .into_iter()
+                .map(|field| (format!("{}.{}", type_name, field.name), field.json_type))
+                .collect(),
+        };
+        for (step, contained_type) in contained {
+            // only the unconstructible types can lead back to the start
+            if self.is_constructible_type(&contained_type, &mut vec![]) {
+                continue;
+            }
+            chain.push(step);
+            if self.find_containment_chain(start, &contained_type, chain, visited) {
+                return true;
+            }
+            chain.pop();
+        }
+        false
+    }
+
+    fn check_value(
+        &mut self,
+        json_type: &JsonType,
+        json_value: &Node,
+        content: &[u8],
+        depth: usize,
+        errors: &mut Vec<JsonTypeError>,
+    ) {
+        let Some(json) = json_value.child(0) else {
+            return;
+        };
+        let mut error = |node: &Node, message: String| {
+            errors.push(JsonTypeError {
+                range: ts_lsp_interop::ts_to_lsp_range(&node.range()),
+                message,
+            })
+        };
+
+        match json_type {
+            JsonType::Any => {}
+            JsonType::Primitive(primitive, type_arguments) => {
+                let type_argument = type_arguments.first().cloned().unwrap_or(JsonType::Any);
+                match primitive {
+                    AdlPrimitive::Int8
+                    | AdlPrimitive::Int16
+                    | AdlPrimitive::Int32
+                    | AdlPrimitive::Int64
+                    | AdlPrimitive::Word8
+                    | AdlPrimitive::Word16
+                    | AdlPrimitive::Word32
+                    | AdlPrimitive::Word64 => {
+                        if !NodeKind::is_json_number(&json) {
+                            return error(&json, expected("an integer", &json));
+                        }
+                        let text = json.utf8_text(content).unwrap_or_default();
+                        let Some((min, max)) = integer_bounds(primitive) else {
+                            return;
+                        };
+                        match text.parse::<i128>() {
+                            Err(_) if text.contains(['.', 'e', 'E']) => {
+                                error(&json, format!("expected an integer, found `{}`", text))
+                            }
+                            Ok(value) if min <= value && value <= max => {}
+                            _ => error(
+                                &json,
+                                format!(
+                                    "`{}` is out of range for {}, which holds {} to {}",
+                                    text,
+                                    primitive.as_str(),
+                                    min,
+                                    max
+                                ),
+                            ),
+                        }
+                    }
+                    AdlPrimitive::Float | AdlPrimitive::Double => {
+                        if !NodeKind::is_json_number(&json) {
+                            return error(&json, expected("a number", &json));
+                        }
+                        let text = json.utf8_text(content).unwrap_or_default();
+                        let is_finite = match primitive {
+                            AdlPrimitive::Float => text.parse::<f32>().is_ok_and(f32::is_finite),
+                            _ => text.parse::<f64>().is_ok_and(f64::is_finite),
+                        };
+                        if !is_finite {
+                            error(
+                                &json,
+                                format!("`{}` is out of range for {}", text, primitive.as_str()),
+                            );
+                        }
+                    }
+                    AdlPrimitive::Bool => {
+                        if !matches!(json.kind(), "true" | "false") {
+                            error(&json, expected("a boolean", &json));
+                        }
+                    }
+                    AdlPrimitive::Void | AdlPrimitive::TypeToken => {
+                        if json.kind() != "null" {
+                            error(&json, expected("`null`", &json));
+                        }
+                    }
+                    AdlPrimitive::String | AdlPrimitive::Bytes => {
+                        if !NodeKind::is_json_string(&json) {
+                            error(&json, expected("a string", &json));
+                        }
+                    }
+                    AdlPrimitive::Json => {}
+                    AdlPrimitive::Vector => {
+                        if !NodeKind::is_json_array(&json) {
+                            return error(&json, expected("an array", &json));
+                        }
+                        for element in json
+                            .named_children(&mut json.walk())
+                            .filter(NodeKind::is_json_value)
+                        {
+                            self.check_value(&type_argument, &element, content, depth, errors);
+                        }
+                    }
+                    AdlPrimitive::StringMap => {
+                        if !NodeKind::is_json_object(&json) {
+                            return error(&json, expected("an object", &json));
+                        }
+                        for value in json
+                            .named_children(&mut json.walk())
+                            .filter_map(|pair| pair.named_child(1))
+                        {
+                            self.check_value(&type_argument, &value, content, depth, errors);
+                        }
+                    }
+                    AdlPrimitive::Nullable => {
+                        if json.kind() != "null" {
+                            self.check_value(&type_argument, json_value, content, depth, errors);
+                        }
+                    }
+                }
+            }
+            JsonType::Defined {
+                uri,
+                type_name,
+                type_arguments,
+            } => {
+                if depth > MAX_EXPANSION_DEPTH {
+                    return;
+                }
+                match self.definition(uri, type_name, type_arguments) {
+                    None => {}
+                    Some(Definition::Alias { json_type, .. }) => {
+                        self.check_value(&json_type, json_value, content, depth + 1, errors)
+                    }
+                    Some(Definition::Struct(fields)) => {
+                        if !NodeKind::is_json_object(&json) {
+                            return error(&json, expected("an object", &json));
+                        }
+                        let pairs = object_pairs(&json, content);
+                        for (key, pair, value) in &pairs {
+                            match fields.iter().find(|field| field.json_key == *key) {
+                                Some(field) => {
+                                    self.check_value(&field.json_type, value, content, 0, errors)
+                                }
+                                None => errors.push(JsonTypeError {
+                                    range: ts_lsp_interop::ts_to_lsp_range(&pair.range()),
+                                    message: format!("`{}` has no field `{}`", type_name, key),
+                                }),
+                            }
+                        }
+
+                        let missing_fields: Vec<String> = fields
+                            .iter()
+                            .filter(|field| {
+                                !field.has_default
+                                    && !pairs.iter().any(|(key, _, _)| *key == field.json_key)
+                            })
+                            .filter(|field| !self.has_type_default(&field.json_type, 0))
+                            .map(|field| format!("`{}`", field.json_key))
+                            .collect();
+                        if !missing_fields.is_empty() {
+                            errors.push(JsonTypeError {
+                                range: ts_lsp_interop::ts_to_lsp_range(&json.range()),
+                                message: format!(
+                                    "missing {} {} of `{}`",
+                                    if missing_fields.len() == 1 {
+                                        "field"
+                                    } else {
+                                        "fields"
+                                    },
+                                    missing_fields.join(", "),
+                                    type_name
+                                ),
+                            });
+                        }
+                    }
+                    Some(Definition::Union(variants)) => {
+                        // variants without a value can be written as a bare string
+                        if NodeKind::is_json_string(&json) {
+                            let key = serde_json::from_str::<String>(
+                                json.utf8_text(content).unwrap_or_default(),
+                            )
+                            .unwrap_or_default();
+                            let is_void_variant = variants.iter().any(|variant| {
+                                variant.json_key == key
+                                    && matches!(
+                                        variant.json_type,
+                                        JsonType::Primitive(AdlPrimitive::Void, _)
+                                    )
+                            });
+                            if !is_void_variant {
+                                error(
+                                    &json,
+                                    format!(
+                                        "`{}` has no variant `{}` without a value",
+                                        type_name, key
+                                    ),
+                                );
+                            }
+                            return;
+                        }
+                        if !NodeKind::is_json_object(&json) {
+                            return error(&json, expected("an object", &json));
+                        }
+
+                        let pairs = object_pairs(&json, content);
+                        let [(key, pair, value)] = &pairs[..] else {
+                            return error(
+                                &json,
+                                format!(
+                                    "expected exactly one variant of `{}`, found {}",
+                                    type_name,
+                                    pairs.len()
+                                ),
+                            );
+                        };
+                        match variants.iter().find(|variant| variant.json_key == *key) {
+                            Some(variant) => {
+                                self.check_value(&variant.json_type, value, content, 0, errors)
+                            }
+                            None => errors.push(JsonTypeError {
+                                range: ts_lsp_interop::ts_to_lsp_range(&pair.range()),
+                                message: format!("`{}` has no variant `{}`", type_name, key),
+                            }),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The keys (unescaped), pairs and values of a JSON object
+fn object_pairs<'a>(json_object: &Node<'a>, content: &[u8]) -> Vec<(String, Node<'a>, Node<'a>)> {
+    json_object
+        .named_children(&mut json_object.walk())
+        .filter(NodeKind::is_json_object_pair)
+        .filter_map(|pair| {
+            let key_node = pair.named_child(0)?;
+            let value = pair.named_child(1)?;
+            let key = serde_json::from_str(key_node.utf8_text(content).ok()?).ok()?;
+            Some((key, pair, value))
+        })
+        .collect()
+}
+
+fn integer_bounds(primitive: &AdlPrimitive) -> Option<(i128, i128)> {
+    match primitive {
+        AdlPrimitive::Int8 => Some((i8::MIN.into(), i8::MAX.into())),
+        AdlPrimitive::Int16 => Some((i16::MIN.into(), i16::MAX.into())),
+        AdlPrimitive::Int32 => Some((i32::MIN.into(), i32::MAX.into())),
+        AdlPrimitive::Int64 => Some((i64::MIN.into(), i64::MAX.into())),
+        AdlPrimitive::Word8 => Some((0, u8::MAX.into())),
+        AdlPrimitive::Word16 => Some((0, u16::MAX.into())),
+        AdlPrimitive::Word32 => Some((0, u32::MAX.into())),
+        AdlPrimitive::Word64 => Some((0, u64::MAX.into())),
+        _ => None,
+    }
+}
+
+fn expected(description: &str, json: &Node) -> String {
+    let found = match json.kind() {
+        "null" => "`null`",
+        "true" | "false" => "a boolean",
+        _ if NodeKind::is_json_number(json) => "a number",
+        _ if NodeKind::is_json_string(json) => "a string",
+        _ if NodeKind::is_json_array(json) => "an array",
+        _ if NodeKind::is_json_object(json) => "an object",
+        _ => "an invalid value",
+    };
+    format!("expected {}, found {}", description, found)
+}
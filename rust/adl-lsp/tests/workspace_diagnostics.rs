@@ -0,0 +1,168 @@
+//! Runs the language server over stdio, as an editor does, to check the messages it sends
+
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::Path;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+
+use serde_json::{Value, json};
+use tempfile::TempDir;
+
+/// The server process, killed when the test ends even if it fails
+struct LanguageServer {
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl LanguageServer {
+    fn start(search_dirs: &[&Path]) -> Self {
+        let search_dirs: Vec<String> = search_dirs
+            .iter()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .collect();
+        let mut process = Command::new(env!("CARGO_BIN_EXE_adl-lsp"))
+            .args(["--search-dirs", &search_dirs.join(",")])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+        let stdin = process.stdin.take().unwrap();
+        let stdout = BufReader::new(process.stdout.take().unwrap());
+        Self {
+            process,
+            stdin,
+            stdout,
+        }
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.stdin, "Content-Length: {}\r\n\r\n{}", body.len(), body).unwrap();
+        self.stdin.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            assert_ne!(
+                self.stdout.read_line(&mut header).unwrap(),
+                0,
+                "server exited"
+            );
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix("Content-Length: ") {
+                content_length = length.parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        self.stdout.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Receive messages until the response to a request, returning the notifications sent before it
+    fn receive_until_response(&mut self, id: u64) -> (Vec<Value>, Value) {
+        let mut notifications = vec![];
+        loop {
+            let message = self.receive();
+            if message["id"] == id && message.get("method").is_none() {
+                return (notifications, message);
+            }
+            notifications.push(message);
+        }
+    }
+}
+
+impl Drop for LanguageServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+fn write_package(root: &Path, name: &str, files: &[(&str, &str)]) {
+    fs::create_dir_all(root).unwrap();
+    fs::write(
+        root.join("adl-package.json"),
+        json!({ "name": name, "dependencies": [] }).to_string(),
+    )
+    .unwrap();
+    for (path, contents) in files {
+        fs::write(root.join(path), contents).unwrap();
+    }
+}
+
+#[test]
+fn test_workspace_diagnostics_stream_partial_results() {
+    let temp_dir = TempDir::new().unwrap();
+    let app = temp_dir.path().join("app");
+    let common = temp_dir.path().join("common");
+    write_package(
+        &app,
+        "app",
+        &[(
+            "main.adl",
+            "module main {\n    struct Message {\n        Missing author;\n    };\n};\n",
+        )],
+    );
+    write_package(
+        &common,
+        "common",
+        &[(
+            "db.adl",
+            "module db {\n    struct User {\n        String name;\n    };\n};\n",
+        )],
+    );
+
+    let mut server = LanguageServer::start(&[&app, &common]);
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "initialize",
+        "params": { "capabilities": {} },
+    }));
+    server.receive_until_response(1);
+    server.send(json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }));
+
+    server.send(json!({
+        "jsonrpc": "2.0",
+        "id": 2,
+        "method": "workspace/diagnostic",
+        "params": { "previousResultIds": [], "partialResultToken": "token" },
+    }));
+    let (notifications, response) = server.receive_until_response(2);
+
+    // each package is streamed before the final response, which does not repeat them
+    let mut streamed: Vec<(String, usize)> = notifications
+        .iter()
+        .filter(|notification| notification["method"] == "$/progress")
+        .map(|progress| {
+            assert_eq!(progress["params"]["token"], "token");
+            let items = progress["params"]["value"]["items"].as_array().unwrap();
+            assert_eq!(items.len(), 1);
+            (
+                items[0]["uri"].as_str().unwrap().to_string(),
+                items[0]["items"].as_array().unwrap().len(),
+            )
+        })
+        .collect();
+    streamed.sort();
+    let file_uri = |path: &Path| {
+        lsp_types::Url::from_file_path(fs::canonicalize(path).unwrap())
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!(
+        streamed,
+        vec![
+            (file_uri(&app.join("main.adl")), 1),
+            (file_uri(&common.join("db.adl")), 0),
+        ]
+    );
+    assert_eq!(response["result"], json!({ "items": [] }));
+}